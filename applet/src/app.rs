@@ -1,20 +1,32 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
+use crate::ipc;
+use crate::launch;
+use crate::notify::{self, DbusNotifier, Notifier};
 use crate::power;
-use crate::power::PowerAction;
+use crate::power::{Inhibitor, PowerAction, ScheduledShutdown, ShutdownKind};
+use crate::power_profiles::{self, PowerProfiles};
+use crate::supervisor;
 use cosmic::app::{Core, Task};
 use cosmic::applet::{menu_button, padded_control};
-use cosmic::cosmic_config::{Config, CosmicConfigEntry};
+use cosmic::cosmic_config::{Config, ConfigGet, ConfigSet, CosmicConfigEntry};
 use cosmic::cosmic_theme::Spacing;
 use cosmic::iced::window::Id;
-use cosmic::iced::{Limits, Subscription};
+use cosmic::iced::{Alignment, Limits, Subscription, time};
 use cosmic::iced_winit::commands::popup::{destroy_popup, get_popup};
 use cosmic::widget;
 use cosmic::{Application, Element};
-use liblog::{IMAGES, LogoMenuConfig, MenuItemType};
+use liblog::flatpak::{self, Output};
+use liblog::view;
+use liblog::{
+    IMAGES, LAUNCH_LOG_KEY, LAUNCH_LOG_LIMIT, LaunchRecord, LogoMenuConfig, MenuItem, MenuItemType,
+    fl, is_flatpak, is_settings_command,
+};
 use std::fs;
 use std::path::Path;
-use std::process::Command;
+use std::process::{Child, Command};
+use std::sync::Arc;
+use std::time::Duration;
 
 const ID: &str = "dev.cappsy.CosmicExtAppletLogoMenu";
 
@@ -22,16 +34,47 @@ pub struct LogoMenu {
     core: Core,
     popup: Option<Id>,
     config: LogoMenuConfig,
+    pending_action: Option<(PowerAction, Vec<Inhibitor>)>,
+    scheduled_shutdown: Option<ScheduledShutdown>,
+    schedule_prompt: Option<ShutdownKind>,
+    schedule_minutes: String,
+    power_profiles: Option<PowerProfiles>,
+    power_profiles_expanded: bool,
+    // indices of the open submenus
+    expanded_submenus: Vec<Vec<usize>>,
+    notifier: Arc<dyn Notifier>,
+    state: Option<Config>,
+    ipc: Option<ipc::Handle>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     TogglePopup,
     PopupClosed(Id),
-    Run(String),
+    Run(MenuItem),
+    OsdFailed(PowerAction, String),
+    Exited(LaunchRecord),
     Action(power::PowerAction),
-    Zbus(Result<(), zbus::Error>),
+    Inhibitors(power::PowerAction, Result<Vec<Inhibitor>, zbus::Error>),
+    ProceedAction,
+    CancelAction,
+    SchedulePrompt(ShutdownKind),
+    ScheduleMinutes(String),
+    Schedule(ShutdownKind, u64),
+    CancelSchedule,
+    ScheduleUpdate(Option<ScheduledShutdown>),
+    RefreshSchedule,
+    Scheduled(String, Result<Option<ScheduledShutdown>, zbus::Error>),
+    Tick,
+    PowerProfiles(Option<PowerProfiles>),
+    TogglePowerProfiles,
+    ToggleSubmenu(Vec<usize>),
+    SetPowerProfile(String),
+    Zbus(String, Result<(), zbus::Error>),
     ConfigUpdate(LogoMenuConfig),
+    ReloadConfig,
+    ActivateItem(MenuItem),
+    IpcReady(ipc::Handle),
 }
 
 impl Application for LogoMenu {
@@ -49,21 +92,27 @@ impl Application for LogoMenu {
     }
 
     fn init(core: Core, _flags: Self::Flags) -> (Self, Task<Self::Message>) {
-        // Load config
-        let helper = Config::new(ID, LogoMenuConfig::VERSION).ok();
-        let config: LogoMenuConfig = helper
-            .as_ref()
-            .map(|helper| {
-                LogoMenuConfig::get_entry(helper).unwrap_or_else(|(_errors, config)| config)
-            })
-            .unwrap_or_default();
+        let config = LogoMenuConfig::load();
+
+        // Launch history lives in state rather than config
+        let state = Config::new_state(ID, LogoMenuConfig::VERSION).ok();
 
         let app = LogoMenu {
             core,
             popup: None,
             config,
+            pending_action: None,
+            scheduled_shutdown: None,
+            schedule_prompt: None,
+            schedule_minutes: String::new(),
+            power_profiles: None,
+            power_profiles_expanded: false,
+            expanded_submenus: Vec::new(),
+            notifier: Arc::new(DbusNotifier::default()),
+            state,
+            ipc: None,
         };
-        (app, Task::none())
+        (app, refresh_schedule())
     }
 
     fn on_close_requested(&self, id: Id) -> Option<Message> {
@@ -89,6 +138,29 @@ impl Application for LogoMenu {
             cosmic::widget::icon::from_svg_bytes(logo_bytes.0).symbolic(logo_bytes.1)
         };
 
+        // Badge the logo with a countdown while a shutdown is scheduled
+        if let Some(schedule) = &self.scheduled_shutdown {
+            let (icon_size, _) = self.core.applet.suggested_size(true);
+            let (major_padding, minor_padding) = self.core.applet.suggested_padding(true);
+
+            return self
+                .core
+                .applet
+                .autosize_window(
+                    widget::button::custom(
+                        widget::row()
+                            .align_y(Alignment::Center)
+                            .spacing(4)
+                            .push(widget::icon(logo_widget).size(icon_size))
+                            .push(widget::text::caption(schedule.remaining_short())),
+                    )
+                    .padding([minor_padding, major_padding])
+                    .class(cosmic::theme::Button::AppletIcon)
+                    .on_press(Message::TogglePopup),
+                )
+                .into();
+        }
+
         self.core
             .applet
             .icon_button_from_handle(logo_widget)
@@ -101,148 +173,375 @@ impl Application for LogoMenu {
             space_xxs, space_s, ..
         } = cosmic::theme::active().cosmic().spacing;
 
-        // Get the menu from config
-        let config_menuitems = &self.config.menu_items;
-
-        let mut content_list = widget::column().padding([8, 0]).spacing(0);
-        for item in &config_menuitems.items {
-            match item.item_type() {
-                MenuItemType::LaunchAction => {
-                    content_list = content_list.push(
-                        menu_button(widget::text::body(item.label().unwrap_or_default()))
-                            .on_press(Message::Run(item.command().unwrap_or_default())),
+        // Show what is holding up a pending power action instead of the menu
+        if let Some((action, inhibitors)) = &self.pending_action {
+            let mut content_list = widget::column().padding([8, 0]).spacing(space_xxs).push(
+                padded_control(widget::text::heading(fl!(
+                    "inhibited-title",
+                    action = action.as_localized_string()
+                )))
+                .padding([space_xxs, space_s]),
+            );
+            for inhibitor in inhibitors {
+                content_list = content_list.push(
+                    padded_control(
+                        widget::column()
+                            .push(widget::text::body(&inhibitor.who))
+                            .push(widget::text::caption(if inhibitor.mode == "block" {
+                                inhibitor.why.clone()
+                            } else {
+                                fl!("inhibitor-delay", why = inhibitor.why.clone())
+                            })),
                     )
-                }
-                MenuItemType::PowerAction => {
-                    content_list = content_list.push(
-                        menu_button(widget::text::body(item.label().unwrap_or_default())).on_press(
-                            Message::Action(match item.command() {
-                                Some(command) => match command.as_ref() {
-                                    "Lock" => PowerAction::Lock,
-                                    "Logout" => PowerAction::LogOut,
-                                    "Suspend" => PowerAction::Suspend,
-                                    "Restart" => PowerAction::Restart,
-                                    "Shutdown" => PowerAction::Shutdown,
-                                    _ => PowerAction::LogOut,
-                                },
-                                _ => PowerAction::Shutdown,
-                            }),
-                        ),
+                    .padding([space_xxs, space_s]),
+                );
+            }
+            content_list = content_list
+                .push(
+                    padded_control(widget::divider::horizontal::default())
+                        .padding([space_xxs, space_s]),
+                )
+                .push(
+                    padded_control(
+                        widget::row()
+                            .spacing(space_xxs)
+                            .push(widget::horizontal_space())
+                            .push(
+                                widget::button::standard(fl!("cancel"))
+                                    .on_press(Message::CancelAction),
+                            )
+                            .push(
+                                widget::button::destructive(fl!("proceed-anyway"))
+                                    .on_press(Message::ProceedAction),
+                            ),
                     )
-                }
-                MenuItemType::Divider => {
-                    content_list = content_list.push(
-                        padded_control(widget::divider::horizontal::default())
-                            .padding([space_xxs, space_s]),
+                    .padding([space_xxs, space_s]),
+                );
+
+            return self.core.applet.popup_container(content_list).into();
+        }
+
+        // Pick a delay for a scheduled shutdown or restart
+        if let Some(kind) = self.schedule_prompt {
+            let custom_minutes = self
+                .schedule_minutes
+                .trim()
+                .parse::<u64>()
+                .ok()
+                .filter(|m| (1..=power::MAX_SCHEDULE_MINUTES).contains(m));
+            let content_list = widget::column()
+                .padding([8, 0])
+                .spacing(0)
+                .push(
+                    padded_control(widget::text::heading(kind.as_localized_string()))
+                        .padding([space_xxs, space_s]),
+                )
+                .push(
+                    menu_button(widget::text::body(fl!("in-15-minutes")))
+                        .on_press(Message::Schedule(kind, 15)),
+                )
+                .push(
+                    menu_button(widget::text::body(fl!("in-1-hour")))
+                        .on_press(Message::Schedule(kind, 60)),
+                )
+                .push(
+                    padded_control(widget::divider::horizontal::default())
+                        .padding([space_xxs, space_s]),
+                )
+                .push(
+                    padded_control(
+                        widget::row()
+                            .spacing(space_xxs)
+                            .align_y(Alignment::Center)
+                            .push(
+                                widget::text_input(fl!("custom-minutes"), &self.schedule_minutes)
+                                    .on_input(Message::ScheduleMinutes),
+                            )
+                            .push(
+                                widget::button::standard(fl!("cancel"))
+                                    .on_press(Message::CancelAction),
+                            )
+                            .push(widget::button::suggested(fl!("schedule")).on_press_maybe(
+                                custom_minutes.map(|m| Message::Schedule(kind, m)),
+                            )),
                     )
-                }
-            };
+                    .padding([space_xxs, space_s]),
+                );
+
+            return self.core.applet.popup_container(content_list).into();
         }
 
+        let mut content_list = view::popup_column();
+
+        // Pending shutdown, with a way to call it off
+        if let Some(schedule) = &self.scheduled_shutdown {
+            content_list = content_list
+                .push(
+                    padded_control(widget::text::heading(schedule.as_localized_string()))
+                        .padding([space_xxs, space_s]),
+                )
+                .push(
+                    menu_button(widget::text::body(fl!("cancel-scheduled-shutdown")))
+                        .on_press(Message::CancelSchedule),
+                )
+                .push(
+                    padded_control(widget::divider::horizontal::default())
+                        .padding([space_xxs, space_s]),
+                );
+        }
+
+        // Power profile items only show while power-profiles-daemon is running
+        let power_profiles = self
+            .power_profiles
+            .as_ref()
+            .map(|profiles| view::PowerProfilesView {
+                active: &profiles.active,
+                available: &profiles.available,
+                expanded: self.power_profiles_expanded,
+            });
+        let content_list = view::menu_items(
+            content_list,
+            &self.config.menu_items.items,
+            power_profiles,
+            &self.expanded_submenus,
+            view::MenuMessages {
+                item: |_, item: &MenuItem| item_message(item),
+                toggle_profiles: Message::TogglePowerProfiles,
+                set_profile: |profile: &str| Message::SetPowerProfile(profile.to_owned()),
+                toggle_submenu: Message::ToggleSubmenu,
+            },
+        );
+
         self.core.applet.popup_container(content_list).into()
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        Subscription::batch(vec![
+        let mut subscriptions = vec![
             self.core
                 .watch_config(ID)
                 .map(|res| Message::ConfigUpdate(res.config)),
-        ])
+            // logind doesn't signal schedules, so look for ones set elsewhere now and then
+            time::every(Duration::from_secs(60)).map(|_| Message::RefreshSchedule),
+            power_profiles::subscription().map(Message::PowerProfiles),
+            ipc::subscription(),
+        ];
+
+        // Keep the countdown moving while a shutdown is pending
+        if self.scheduled_shutdown.is_some() {
+            subscriptions.push(time::every(Duration::from_secs(15)).map(|_| Message::Tick));
+        }
+
+        Subscription::batch(subscriptions)
     }
 
     fn update(&mut self, message: Self::Message) -> Task<Self::Message> {
         match message {
             Message::TogglePopup => {
-                return if let Some(p) = self.popup.take() {
-                    destroy_popup(p)
+                return if self.popup.is_some() {
+                    self.close_popup()
                 } else {
-                    let new_id = Id::unique();
-                    self.popup.replace(new_id);
-                    let mut popup_settings = self.core.applet.get_popup_settings(
-                        self.core.main_window_id().unwrap(),
-                        new_id,
-                        None,
-                        None,
-                        None,
-                    );
-                    popup_settings.positioner.size_limits = Limits::NONE
-                        .max_width(372.0)
-                        .min_width(300.0)
-                        .min_height(200.0)
-                        .max_height(1080.0);
-                    get_popup(popup_settings)
+                    self.open_popup()
                 };
             }
             Message::Action(action) => {
-                let osd_arg = match action {
-                    power::PowerAction::LogOut => "log-out",
-                    power::PowerAction::Restart => "restart",
-                    power::PowerAction::Shutdown => "shutdown",
-                    _ => return action.perform(),
-                };
-                let is_flatpak = is_flatpak();
-
-                if is_flatpak {
-                    if let Err(_err) = Command::new("flatpak-spawn")
-                        .arg("--host")
-                        .arg("cosmic-osd")
-                        .arg(osd_arg)
-                        .spawn()
-                    {
-                        return action.perform();
+                if !action.is_inhibitable() {
+                    return action.perform();
+                }
+
+                // Check nothing is holding up shutdown before going ahead
+                return Task::perform(power::shutdown_inhibitors(), move |result| {
+                    cosmic::action::app(Message::Inhibitors(action, result))
+                });
+            }
+            Message::Inhibitors(action, result) => match result {
+                Ok(inhibitors) if !inhibitors.is_empty() => {
+                    self.pending_action = Some((action, inhibitors));
+                    // May have come from the D-Bus interface with no popup open
+                    if self.popup.is_none() {
+                        return self.open_popup();
                     }
+                }
+                Ok(_) => return self.run_action(action),
+                Err(e) => {
+                    eprintln!("cosmic-ext-applet-logomenu ERROR: '{}'", e);
+                    return self.run_action(action);
+                }
+            },
+            Message::ProceedAction => {
+                if let Some((action, _)) = self.pending_action.take() {
+                    return self.run_action(action);
+                }
+            }
+            Message::CancelAction => {
+                self.pending_action = None;
+                self.schedule_prompt = None;
+                return self.close_popup();
+            }
+            Message::SchedulePrompt(kind) => {
+                self.schedule_prompt = Some(kind);
+                self.schedule_minutes.clear();
+                if self.popup.is_none() {
+                    return self.open_popup();
+                }
+            }
+            Message::ScheduleMinutes(minutes) => {
+                self.schedule_minutes = minutes;
+            }
+            Message::Schedule(kind, minutes) => {
+                self.schedule_prompt = None;
+                return Task::batch([
+                    Task::perform(power::schedule_shutdown(kind, minutes), move |result| {
+                        cosmic::action::app(Message::Scheduled(kind.as_localized_string(), result))
+                    }),
+                    self.close_popup(),
+                ]);
+            }
+            Message::CancelSchedule => {
+                return Task::batch([
+                    Task::perform(power::cancel_scheduled_shutdown(), |result| {
+                        let label = fl!("cancel-scheduled-shutdown");
+                        cosmic::action::app(Message::Scheduled(label, result))
+                    }),
+                    self.close_popup(),
+                ]);
+            }
+            Message::ScheduleUpdate(schedule) => {
+                self.scheduled_shutdown = schedule;
+            }
+            Message::RefreshSchedule => {
+                return refresh_schedule();
+            }
+            Message::Scheduled(label, result) => match result {
+                Ok(schedule) => self.scheduled_shutdown = schedule,
+                Err(e) => return self.report_failure(label, e.to_string()),
+            },
+            Message::Tick => {}
+            Message::PowerProfiles(profiles) => {
+                self.power_profiles = profiles;
+            }
+            Message::TogglePowerProfiles => {
+                self.power_profiles_expanded = !self.power_profiles_expanded;
+            }
+            Message::ToggleSubmenu(path) => {
+                if let Some(pos) = self.expanded_submenus.iter().position(|open| *open == path) {
+                    self.expanded_submenus.remove(pos);
                 } else {
-                    if let Err(_err) = Command::new("cosmic-osd").arg(osd_arg).spawn() {
-                        return action.perform();
-                    }
+                    self.expanded_submenus.push(path);
                 }
-
-                return close_popup(self.popup);
             }
-            Message::Zbus(result) => {
+            Message::SetPowerProfile(profile) => {
+                return Task::perform(power_profiles::set_active(profile), |result| {
+                    cosmic::action::app(Message::Zbus(fl!("power-profiles"), result))
+                });
+            }
+            Message::Zbus(label, result) => {
                 if let Err(e) = result {
-                    eprintln!("cosmic-ext-applet-logomenu ERROR: '{}'", e);
+                    return self.report_failure(label, e.to_string());
                 }
             }
             Message::PopupClosed(id) => {
                 if self.popup.as_ref() == Some(&id) {
                     self.popup = None;
+                    self.pending_action = None;
+                    self.schedule_prompt = None;
+                    self.expanded_submenus.clear();
+                    return self.notify_popup_visible();
                 }
             }
-            Message::Run(action) => {
-                if is_flatpak()
-                    && action != "cosmic-ext-logomenu-settings"
-                    && action != "cosmic-logomenu-settings"
-                {
-                    match Command::new("flatpak-spawn")
-                        .arg("--host")
-                        .arg("sh")
-                        .arg("-c")
-                        .arg(&action)
-                        .spawn()
-                    {
-                        Ok(_) => {}
-                        Err(e) => eprintln!("Error executing command: {}", e),
-                    }
+            Message::Run(item) => {
+                let label = item.label().unwrap_or_default();
+                let action = item.command().unwrap_or_default();
+                let mut tasks = vec![self.close_popup()];
+
+                if is_flatpak() && !is_settings_command(&action) {
+                    tasks.push(Task::perform(
+                        supervisor::supervise_host(label.clone(), action),
+                        move |result| match result {
+                            Ok(record) => cosmic::action::app(Message::Exited(record)),
+                            Err(e) => cosmic::action::app(Message::Zbus(label, Err(e))),
+                        },
+                    ));
                 } else {
-                    match Command::new("sh")
-                        .arg("-c")
-                        .arg(if &action == "cosmic-logomenu-settings" {
-                            "cosmic-ext-logomenu-settings"
-                        } else {
-                            &action
-                        })
-                        .spawn()
-                    {
-                        Ok(_) => {}
-                        Err(e) => eprintln!("Error executing command: {}", e),
+                    let action = if is_settings_command(&action) {
+                        String::from("cosmic-ext-logomenu-settings")
+                    } else {
+                        action
+                    };
+                    let spawned = supervisor::StderrLog::create().and_then(|(log, file)| {
+                        let (child, gate) = launch::spawn(&action, &item.resources, file)?;
+                        Ok((child, gate, log))
+                    });
+                    match spawned {
+                        Ok((child, gate, stderr)) => {
+                            if is_flatpak() {
+                                gate.open();
+                            } else {
+                                tasks.push(
+                                    Task::future(launch::move_to_scope(
+                                        action.clone(),
+                                        child.id(),
+                                        item.resources(),
+                                        gate,
+                                    ))
+                                    .discard(),
+                                );
+                            }
+                            tasks.push(supervise(label, action, child, stderr));
+                        }
+                        Err(e) => tasks.push(self.report_failure(label, e.to_string())),
                     };
                 }
 
-                return close_popup(self.popup);
+                return Task::batch(tasks);
+            }
+            Message::OsdFailed(action, error) => {
+                return self.osd_failed(action, error);
             }
+            Message::Exited(record) => {
+                let failure = (!record.success()).then(|| {
+                    let error = match (record.stderr.last(), record.exit_code) {
+                        (Some(line), _) => line.clone(),
+                        (None, Some(code)) => fl!("exited-with-code", code = code),
+                        (None, None) => fl!("exited-by-signal"),
+                    };
+                    (record.label.clone(), error)
+                });
+
+                // read back first, the log may have been cleared in settings
+                if let Some(state) = &self.state {
+                    let mut launch_log: Vec<LaunchRecord> =
+                        state.get(LAUNCH_LOG_KEY).unwrap_or_default();
+                    launch_log.push(record);
+                    if launch_log.len() > LAUNCH_LOG_LIMIT {
+                        let excess = launch_log.len() - LAUNCH_LOG_LIMIT;
+                        launch_log.drain(..excess);
+                    }
+                    if let Err(e) = state.set(LAUNCH_LOG_KEY, &launch_log) {
+                        eprintln!("cosmic-ext-applet-logomenu ERROR: '{}'", e);
+                    }
+                }
+
+                if let Some((label, error)) = failure {
+                    return self.report_failure(label, error);
+                }
+            }
+            // open submenus are kept by position, which a new config can shift
             Message::ConfigUpdate(config) => {
                 self.config = config;
+                self.expanded_submenus.clear();
+            }
+            Message::ReloadConfig => {
+                self.config = LogoMenuConfig::load();
+                self.expanded_submenus.clear();
+            }
+            Message::ActivateItem(item) => {
+                if let Some(message) = item_message(&item) {
+                    return self.update(message);
+                }
+            }
+            Message::IpcReady(handle) => {
+                self.ipc = Some(handle);
+                return self.notify_popup_visible();
             }
         }
         Task::none()
@@ -253,20 +552,139 @@ impl Application for LogoMenu {
     }
 }
 
-fn close_popup(mut popup: Option<Id>) -> Task<Message> {
-    if let Some(p) = popup.take() {
-        destroy_popup(p)
-    } else {
-        Task::none()
+impl LogoMenu {
+    fn open_popup(&mut self) -> Task<Message> {
+        let new_id = Id::unique();
+        self.popup.replace(new_id);
+        let mut popup_settings = self.core.applet.get_popup_settings(
+            self.core.main_window_id().unwrap(),
+            new_id,
+            None,
+            None,
+            None,
+        );
+        popup_settings.positioner.size_limits = Limits::NONE
+            .max_width(view::POPUP_MAX_WIDTH)
+            .min_width(view::POPUP_MIN_WIDTH)
+            .min_height(200.0)
+            .max_height(1080.0);
+        Task::batch([
+            get_popup(popup_settings),
+            self.notify_popup_visible(),
+            refresh_schedule(),
+        ])
+    }
+
+    fn close_popup(&mut self) -> Task<Message> {
+        if let Some(p) = self.popup.take() {
+            self.expanded_submenus.clear();
+            Task::batch([destroy_popup(p), self.notify_popup_visible()])
+        } else {
+            Task::none()
+        }
+    }
+
+    // Keep the PopupVisible property on the bus in sync
+    fn notify_popup_visible(&self) -> Task<Message> {
+        match &self.ipc {
+            Some(handle) => {
+                Task::future(handle.clone().set_popup_visible(self.popup.is_some())).discard()
+            }
+            None => Task::none(),
+        }
+    }
+
+    // Log a failed item and let the user know about it unless they opted out
+    fn report_failure(&self, label: String, error: String) -> Task<Message> {
+        eprintln!("cosmic-ext-applet-logomenu ERROR: '{}': {}", label, error);
+        if !self.config.notify_on_failure {
+            return Task::none();
+        }
+        Task::future(notify::failure(self.notifier.clone(), label, error)).discard()
+    }
+
+    fn run_action(&mut self, action: PowerAction) -> Task<Message> {
+        let osd_arg = match action {
+            power::PowerAction::LogOut => "log-out",
+            power::PowerAction::Restart => "restart",
+            power::PowerAction::Shutdown => "shutdown",
+            _ => return action.perform(),
+        };
+        if is_flatpak() {
+            return Task::batch([
+                Task::perform(
+                    flatpak::spawn(&["cosmic-osd", osd_arg], Output::default(), 0),
+                    move |result| match result {
+                        Ok(_) => cosmic::Action::None,
+                        Err(e) => cosmic::action::app(Message::OsdFailed(action, e.to_string())),
+                    },
+                ),
+                self.close_popup(),
+            ]);
+        }
+
+        if let Err(err) = Command::new("cosmic-osd").arg(osd_arg).spawn() {
+            return self.osd_failed(action, err.to_string());
+        }
+
+        self.close_popup()
+    }
+
+    // Without cosmic-osd there's no confirmation dialog, so go straight ahead
+    fn osd_failed(&self, action: PowerAction, error: String) -> Task<Message> {
+        Task::batch([
+            self.report_failure(action.as_localized_string(), error),
+            action.perform(),
+        ])
     }
 }
 
-#[cfg(feature = "flatpak")]
-fn is_flatpak() -> bool {
-    true
+// Reap a launched process and log how it went
+fn supervise(
+    label: String,
+    command: String,
+    child: Child,
+    stderr: supervisor::StderrLog,
+) -> Task<Message> {
+    Task::perform(
+        supervisor::supervise(label, command, child, stderr),
+        |record| cosmic::action::app(Message::Exited(record)),
+    )
 }
 
-#[cfg(not(feature = "flatpak"))]
-fn is_flatpak() -> bool {
-    false
+// What pressing a launch or power item does
+fn item_message(item: &MenuItem) -> Option<Message> {
+    match item.item_type() {
+        MenuItemType::LaunchAction => Some(Message::Run(item.clone())),
+        MenuItemType::PowerAction => Some(match item.command().as_deref() {
+            Some("ScheduleShutdown") => Message::SchedulePrompt(ShutdownKind::PowerOff),
+            Some("ScheduleRestart") => Message::SchedulePrompt(ShutdownKind::Reboot),
+            command => Message::Action(match command {
+                Some(command) => match command {
+                    "Lock" => PowerAction::Lock,
+                    "Logout" => PowerAction::LogOut,
+                    "Suspend" => PowerAction::Suspend,
+                    "Restart" => PowerAction::Restart,
+                    "Shutdown" => PowerAction::Shutdown,
+                    _ => PowerAction::LogOut,
+                },
+                _ => PowerAction::Shutdown,
+            }),
+        }),
+        MenuItemType::PowerProfiles
+        | MenuItemType::Divider
+        | MenuItemType::Header
+        | MenuItemType::Submenu => None,
+    }
+}
+
+// Read logind's schedule again, keeping the last known one if that fails
+fn refresh_schedule() -> Task<Message> {
+    Task::perform(power::scheduled_shutdown(), |result| match result {
+        Ok(schedule) => cosmic::action::app(Message::ScheduleUpdate(schedule)),
+        Err(e) => {
+            eprintln!("cosmic-ext-applet-logomenu ERROR: '{}'", e);
+            cosmic::Action::None
+        }
+    })
 }
@@ -0,0 +1,178 @@
+// SPDX-License-Identifier: GPL-3.0-only
+//
+// Session bus interface so shortcuts and scripts can drive the applet.
+// Every instance owns dev.cappsy.CosmicExtAppletLogoMenu.<Panel>, and the
+// first one to start also owns dev.cappsy.CosmicExtAppletLogoMenu.
+
+use crate::app::Message;
+use cosmic::iced::futures::{SinkExt, channel::mpsc::Sender};
+use cosmic::iced::{Subscription, stream};
+use liblog::{LogoMenuConfig, MenuItemType};
+use zbus::fdo::RequestNameFlags;
+use zbus::{Connection, fdo, interface};
+
+const BUS_NAME: &str = "dev.cappsy.CosmicExtAppletLogoMenu";
+const OBJECT_PATH: &str = "/dev/cappsy/CosmicExtAppletLogoMenu";
+
+// Lets the app update the exported state once the interface is up
+#[derive(Debug, Clone)]
+pub struct Handle {
+    connection: Connection,
+}
+impl Handle {
+    pub async fn set_popup_visible(self, visible: bool) {
+        let Ok(iface) = self
+            .connection
+            .object_server()
+            .interface::<_, LogoMenuInterface>(OBJECT_PATH)
+            .await
+        else {
+            return;
+        };
+        let mut iface_mut = iface.get_mut().await;
+        if iface_mut.popup_visible != visible {
+            iface_mut.popup_visible = visible;
+            if let Err(e) = iface_mut
+                .popup_visible_changed(iface.signal_context())
+                .await
+            {
+                eprintln!("cosmic-ext-applet-logomenu ERROR: '{}'", e);
+            }
+        }
+    }
+}
+
+struct LogoMenuInterface {
+    output: Sender<Message>,
+    popup_visible: bool,
+}
+impl LogoMenuInterface {
+    async fn send(&mut self, message: Message) -> fdo::Result<()> {
+        self.output
+            .send(message)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+}
+
+#[interface(name = "dev.cappsy.CosmicExtAppletLogoMenu")]
+impl LogoMenuInterface {
+    async fn toggle_popup(&mut self) -> fdo::Result<()> {
+        self.send(Message::TogglePopup).await
+    }
+
+    // Activate an item by its position in the menu or by its label
+    async fn activate_item(&mut self, item: &str) -> fdo::Result<()> {
+        let items = LogoMenuConfig::load().menu_items.items;
+        let index = match item.parse::<usize>() {
+            Ok(index) if index < items.len() => index,
+            Ok(_) => return Err(fdo::Error::InvalidArgs(format!("no item at {}", item))),
+            Err(_) => items
+                .iter()
+                .position(|menu_item| menu_item.label().as_deref() == Some(item))
+                .ok_or_else(|| fdo::Error::InvalidArgs(format!("no item labelled {}", item)))?,
+        };
+        if !items[index].enabled {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "item {} is disabled",
+                item
+            )));
+        }
+        // the item itself goes along, so the app runs what was looked up here
+        // even if its copy of the config hasn't caught up yet
+        self.send(Message::ActivateItem(items[index].clone())).await
+    }
+
+    // Each item as (index, type, label, command)
+    async fn list_items(&self) -> Vec<(u32, String, String, String)> {
+        LogoMenuConfig::load()
+            .menu_items
+            .items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let item_type = match item.item_type() {
+                    MenuItemType::LaunchAction => "launch",
+                    MenuItemType::PowerAction => "power",
+                    MenuItemType::PowerProfiles => "power-profiles",
+                    MenuItemType::Divider => "divider",
+                    MenuItemType::Header => "header",
+                    MenuItemType::Submenu => "submenu",
+                };
+                (
+                    i as u32,
+                    item_type.to_owned(),
+                    item.label().unwrap_or_default(),
+                    item.command().unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    async fn reload_config(&mut self) -> fdo::Result<()> {
+        self.send(Message::ReloadConfig).await
+    }
+
+    #[zbus(property)]
+    async fn popup_visible(&self) -> bool {
+        self.popup_visible
+    }
+}
+
+pub fn subscription() -> Subscription<Message> {
+    Subscription::run_with_id(
+        "logomenu-ipc",
+        stream::channel(8, |mut output| async move {
+            match serve(output.clone()).await {
+                Ok(connection) => {
+                    let _ = output.send(Message::IpcReady(Handle { connection })).await;
+                }
+                Err(e) => eprintln!("cosmic-ext-applet-logomenu ERROR: '{}'", e),
+            }
+            std::future::pending::<()>().await;
+        }),
+    )
+}
+
+async fn serve(output: Sender<Message>) -> zbus::Result<Connection> {
+    let iface = LogoMenuInterface {
+        output,
+        popup_visible: false,
+    };
+    let connection = zbus::connection::Builder::session()?
+        .serve_at(OBJECT_PATH, iface)?
+        .build()
+        .await?;
+
+    // Queue for the shared name so another instance takes over if this one goes
+    // away. Neither name failing keeps the other from being requested.
+    if let Err(e) = connection
+        .request_name_with_flags(BUS_NAME, RequestNameFlags::AllowReplacement.into())
+        .await
+    {
+        eprintln!("cosmic-ext-applet-logomenu ERROR: '{}'", e);
+    }
+    if let Err(e) = connection
+        .request_name_with_flags(instance_name(), RequestNameFlags::DoNotQueue.into())
+        .await
+    {
+        eprintln!("cosmic-ext-applet-logomenu ERROR: '{}'", e);
+    }
+
+    Ok(connection)
+}
+
+// Per-panel name, e.g. dev.cappsy.CosmicExtAppletLogoMenu.Panel
+fn instance_name() -> String {
+    let panel = std::env::var("COSMIC_PANEL_NAME").unwrap_or_default();
+    let panel: String = panel
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect();
+    let panel = match panel.chars().next() {
+        None => format!("Pid{}", std::process::id()),
+        Some(c) if c.is_ascii_digit() => format!("_{}", panel),
+        Some(_) => panel,
+    };
+    format!("{}.{}", BUS_NAME, panel)
+}
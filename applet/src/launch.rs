@@ -0,0 +1,193 @@
+// SPDX-License-Identifier: GPL-3.0-only
+//
+// Launch menu items in their own systemd user scopes, so they don't share the
+// applet's cgroup and get taken down along with it
+
+use cosmic::iced::futures::StreamExt;
+use cosmic::iced::futures::channel::oneshot;
+use cosmic::iced::futures::future::{self, Either};
+use liblog::ResourceLimits;
+use rustix::pipe::{PipeFlags, pipe_with};
+use rustix::process::{Pid, setpriority_process};
+use std::fs::File;
+use std::hash::{BuildHasher, Hasher, RandomState};
+use std::io::{self, ErrorKind, Read, Write};
+use std::os::fd::AsRawFd;
+use std::os::unix::process::CommandExt;
+use std::pin::pin;
+use std::process::{Child, Command};
+use std::thread;
+use std::time::Duration;
+use zbus::zvariant::{OwnedObjectPath, Value};
+use zbus::{Connection, proxy};
+
+// How long a launch waits on systemd before running outside a scope
+const SCOPE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[proxy(
+    interface = "org.freedesktop.systemd1.Manager",
+    default_service = "org.freedesktop.systemd1",
+    default_path = "/org/freedesktop/systemd1"
+)]
+trait SystemdManager {
+    // Signals are only sent to clients that asked for them
+    fn subscribe(&self) -> zbus::Result<()>;
+
+    fn start_transient_unit(
+        &self,
+        name: &str,
+        mode: &str,
+        properties: &[(&str, Value<'_>)],
+        aux: &[(&str, &[(&str, Value<'_>)])],
+    ) -> zbus::Result<OwnedObjectPath>;
+
+    #[zbus(signal)]
+    fn job_removed(
+        &self,
+        id: u32,
+        job: OwnedObjectPath,
+        unit: String,
+        result: String,
+    ) -> zbus::Result<()>;
+}
+
+// Holds a launched process back until it's been placed in its scope
+pub struct Gate(File);
+impl Gate {
+    pub fn open(self) {
+        // dropping would do too, but only once other launches forked in the
+        // meantime have exec'd and closed their copies of this end
+        let _ = (&self.0).write_all(&[1]);
+    }
+}
+
+// Run a shell command with stderr going to its launch log, applying the item's
+// niceness straight away. The command doesn't start until the gate is opened.
+pub fn spawn(command: &str, resources: &ResourceLimits, stderr: File) -> io::Result<(Child, Gate)> {
+    let (wait, gate) = pipe_with(PipeFlags::CLOEXEC)?;
+    let (wait, gate) = (File::from(wait), File::from(gate));
+    let gate_fd = gate.as_raw_fd();
+    let mut sh = Command::new("sh");
+    sh.arg("-c").arg(command).stderr(stderr);
+    // SAFETY: only close(2) and read(2) run between fork and exec
+    unsafe {
+        sh.pre_exec(move || {
+            // the child's own copy of the gate would keep it from ever closing
+            rustix::io::close(gate_fd);
+            let mut byte = [0];
+            loop {
+                match (&wait).read(&mut byte) {
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    _ => return Ok(()),
+                }
+            }
+        });
+    }
+    let child = sh.spawn()?;
+
+    match resources.nice {
+        Some(nice) if ResourceLimits::NICE.contains(&nice) => {
+            if let Err(e) = setpriority_process(Pid::from_raw(child.id() as i32), nice) {
+                eprintln!("cosmic-ext-applet-logomenu ERROR: '{}'", e);
+            }
+        }
+        Some(nice) => eprintln!(
+            "cosmic-ext-applet-logomenu ERROR: 'niceness {} is out of range'",
+            nice
+        ),
+        None => {}
+    }
+
+    Ok((child, Gate(gate)))
+}
+
+// Move a launched process into app-<id>-<random>.scope on the user manager,
+// then let it run. If there is no user systemd the process just carries on as
+// a plain child of the applet.
+pub async fn move_to_scope(command: String, pid: u32, resources: ResourceLimits, gate: Gate) {
+    let scope = pin!(start_scope(&command, pid, &resources));
+    match future::select(scope, timer(SCOPE_TIMEOUT)).await {
+        Either::Left((Err(e), _)) => eprintln!("cosmic-ext-applet-logomenu ERROR: '{}'", e),
+        Either::Left((Ok(()), _)) => {}
+        Either::Right(_) => {
+            eprintln!(
+                "cosmic-ext-applet-logomenu ERROR: 'no scope for {}'",
+                command
+            )
+        }
+    }
+    gate.open();
+}
+
+async fn start_scope(command: &str, pid: u32, resources: &ResourceLimits) -> zbus::Result<()> {
+    let connection = Connection::session().await?;
+    let systemd = SystemdManagerProxy::new(&connection).await?;
+    // the process is only in the scope once the start job is done
+    systemd.subscribe().await?;
+    let mut jobs = systemd.receive_job_removed().await?;
+
+    let unit_name = format!(
+        "app-{}-{:016x}.scope",
+        unit_id(command),
+        RandomState::new().build_hasher().finish()
+    );
+    let mut properties: Vec<(&str, Value<'_>)> = vec![
+        ("PIDs", Value::from(vec![pid])),
+        ("CollectMode", Value::from("inactive-or-failed")),
+        ("Description", Value::from(command)),
+    ];
+    if let Some(memory_max) = resources.memory_max {
+        properties.push(("MemoryMax", Value::from(memory_max)));
+    }
+    match resources.cpu_weight {
+        Some(cpu_weight) if ResourceLimits::CPU_WEIGHT.contains(&cpu_weight) => {
+            properties.push(("CPUWeight", Value::from(cpu_weight)));
+        }
+        // systemd would refuse the whole scope over it
+        Some(cpu_weight) => eprintln!(
+            "cosmic-ext-applet-logomenu ERROR: 'CPU weight {} is out of range'",
+            cpu_weight
+        ),
+        None => {}
+    }
+
+    let job = systemd
+        .start_transient_unit(&unit_name, "fail", &properties, &[])
+        .await?;
+    while let Some(removed) = jobs.next().await {
+        if removed.args()?.job == job {
+            break;
+        }
+    }
+    Ok(())
+}
+
+// Resolves once the duration has passed
+pub fn timer(duration: Duration) -> oneshot::Receiver<()> {
+    let (tx, rx) = oneshot::channel();
+    thread::spawn(move || {
+        thread::sleep(duration);
+        let _ = tx.send(());
+    });
+    rx
+}
+
+// Name of the program being run, escaped for use in a unit name
+fn unit_id(command: &str) -> String {
+    let program = command
+        .split_whitespace()
+        .next()
+        .and_then(|program| program.rsplit('/').next())
+        .unwrap_or("logomenu");
+
+    program
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
@@ -2,8 +2,17 @@
 
 use app::LogoMenu;
 mod app;
+mod ipc;
+mod launch;
+mod notify;
 mod power;
+mod power_profiles;
+mod supervisor;
 
 fn main() -> cosmic::iced::Result {
+    // Menu labels stored as message IDs are looked up in these languages
+    let requested_languages = i18n_embed::DesktopLanguageRequester::requested_languages();
+    liblog::init(&requested_languages);
+
     cosmic::applet::run::<LogoMenu>(())
 }
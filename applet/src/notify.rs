@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: GPL-3.0-only
+//
+// Desktop notifications for actions that failed
+
+use cosmic::iced::futures::future::BoxFuture;
+use liblog::fl;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use zbus::zvariant::Value;
+use zbus::{Connection, proxy};
+
+const APP_ICON: &str = "dev.cappsy.CosmicExtAppletLogoMenu";
+
+#[proxy(
+    interface = "org.freedesktop.Notifications",
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications"
+)]
+trait Notifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, Value<'_>>,
+        expire_timeout: i32,
+    ) -> zbus::Result<u32>;
+}
+
+// Anything that can put a notification in front of the user
+pub trait Notifier: Send + Sync {
+    fn notify(&self, summary: String, body: String) -> BoxFuture<'static, zbus::Result<()>>;
+}
+
+// Sends notifications over org.freedesktop.Notifications, connecting to the
+// session bus the first time and reusing that connection afterwards
+#[derive(Debug, Clone, Default)]
+pub struct DbusNotifier {
+    connection: Arc<Mutex<Option<Connection>>>,
+}
+impl DbusNotifier {
+    // Send over an existing connection instead of the session bus
+    pub fn with_connection(connection: Connection) -> Self {
+        Self {
+            connection: Arc::new(Mutex::new(Some(connection))),
+        }
+    }
+
+    async fn connection(&self) -> zbus::Result<Connection> {
+        let existing = self.connection.lock().unwrap().clone();
+        if let Some(connection) = existing {
+            return Ok(connection);
+        }
+        let connection = Connection::session().await?;
+        Ok(self
+            .connection
+            .lock()
+            .unwrap()
+            .get_or_insert(connection)
+            .clone())
+    }
+}
+impl Notifier for DbusNotifier {
+    fn notify(&self, summary: String, body: String) -> BoxFuture<'static, zbus::Result<()>> {
+        let notifier = self.clone();
+        Box::pin(async move {
+            let connection = notifier.connection().await?;
+            let notifications = NotificationsProxy::new(&connection).await?;
+            notifications
+                .notify(
+                    &fl!("app-title"),
+                    0,
+                    APP_ICON,
+                    &summary,
+                    &body,
+                    &[],
+                    HashMap::new(),
+                    -1,
+                )
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+// Notify that the item with the given label failed
+pub async fn failure(notifier: Arc<dyn Notifier>, label: String, error: String) {
+    if let Err(e) = notifier
+        .notify(fl!("action-failed", label = label), error)
+        .await
+    {
+        eprintln!("cosmic-ext-applet-logomenu ERROR: '{}'", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zbus::{Guid, interface};
+
+    // Records what it's sent, in place of the desktop's notification server
+    #[derive(Clone, Default)]
+    struct MockNotifications {
+        received: Arc<Mutex<Vec<(String, String)>>>,
+    }
+
+    #[interface(name = "org.freedesktop.Notifications")]
+    impl MockNotifications {
+        #[allow(clippy::too_many_arguments)]
+        fn notify(
+            &self,
+            _app_name: &str,
+            _replaces_id: u32,
+            _app_icon: &str,
+            summary: &str,
+            body: &str,
+            _actions: Vec<&str>,
+            _hints: HashMap<&str, Value<'_>>,
+            _expire_timeout: i32,
+        ) -> u32 {
+            let mut received = self.received.lock().unwrap();
+            received.push((summary.to_owned(), body.to_owned()));
+            received.len() as u32
+        }
+    }
+
+    #[tokio::test]
+    async fn failure_reaches_the_notification_server() {
+        let mock = MockNotifications::default();
+        let received = mock.received.clone();
+
+        let (server, client) = tokio::net::UnixStream::pair().unwrap();
+        let server = zbus::connection::Builder::unix_stream(server)
+            .server(Guid::generate())
+            .unwrap()
+            .p2p()
+            .serve_at("/org/freedesktop/Notifications", mock)
+            .unwrap()
+            .build();
+        let client = zbus::connection::Builder::unix_stream(client).p2p().build();
+        let (_server, client) = tokio::try_join!(server, client).unwrap();
+
+        let notifier = Arc::new(DbusNotifier::with_connection(client));
+        failure(
+            notifier,
+            String::from("Terminal"),
+            String::from("not found"),
+        )
+        .await;
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert!(received[0].0.contains("Terminal"));
+        assert_eq!(received[0].1, "not found");
+    }
+}
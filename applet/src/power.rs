@@ -4,15 +4,19 @@
 // https://github.com/pop-os/cosmic-applets
 
 pub mod cosmic_session;
+pub mod logind;
 
 use crate::app;
 use cosmic_session::CosmicSessionProxy;
+use liblog::fl;
+use logind::LogindManagerProxy;
 use logind_zbus::{
     manager::ManagerProxy,
     session::{SessionClass, SessionProxy, SessionType},
     user::UserProxy,
 };
 use rustix::process::getuid;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use zbus::Connection;
 
 #[derive(Debug, Clone, Copy)]
@@ -25,7 +29,8 @@ pub enum PowerAction {
 }
 impl PowerAction {
     pub fn perform(self) -> cosmic::iced::Task<cosmic::Action<app::Message>> {
-        let msg = |m| cosmic::action::app(app::Message::Zbus(m));
+        let label = self.as_localized_string();
+        let msg = move |m| cosmic::action::app(app::Message::Zbus(label, m));
         match self {
             PowerAction::Lock => cosmic::iced::Task::perform(lock(), msg),
             PowerAction::LogOut => cosmic::iced::Task::perform(log_out(), msg),
@@ -34,6 +39,45 @@ impl PowerAction {
             PowerAction::Shutdown => cosmic::iced::Task::perform(shutdown(), msg),
         }
     }
+    // Actions that logind shutdown inhibitors should be checked for first
+    pub fn is_inhibitable(self) -> bool {
+        matches!(
+            self,
+            PowerAction::LogOut | PowerAction::Restart | PowerAction::Shutdown
+        )
+    }
+    pub fn as_localized_string(self) -> String {
+        match self {
+            PowerAction::Lock => fl!("lock"),
+            PowerAction::LogOut => fl!("logout"),
+            PowerAction::Suspend => fl!("suspend"),
+            PowerAction::Restart => fl!("restart"),
+            PowerAction::Shutdown => fl!("shutdown"),
+        }
+    }
+}
+
+// An application holding a logind delay or block lock on shutdown
+#[derive(Debug, Clone)]
+pub struct Inhibitor {
+    pub who: String,
+    pub why: String,
+    pub mode: String,
+}
+
+pub async fn shutdown_inhibitors() -> zbus::Result<Vec<Inhibitor>> {
+    let connection = Connection::system().await?;
+    let manager_proxy = LogindManagerProxy::new(&connection).await?;
+    let inhibitors = manager_proxy
+        .list_inhibitors()
+        .await?
+        .into_iter()
+        .filter(|(what, _, _, mode, _, _)| {
+            what.split(':').any(|w| w == "shutdown") && (mode == "block" || mode == "delay")
+        })
+        .map(|(_, who, why, mode, _, _)| Inhibitor { who, why, mode })
+        .collect();
+    Ok(inhibitors)
 }
 
 async fn restart() -> zbus::Result<()> {
@@ -93,3 +137,116 @@ async fn log_out() -> zbus::Result<()> {
     cosmic_session.exit().await?;
     Ok(())
 }
+
+// Shutdown types understood by logind's ScheduleShutdown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownKind {
+    PowerOff,
+    Reboot,
+}
+impl ShutdownKind {
+    fn as_logind(self) -> &'static str {
+        match self {
+            ShutdownKind::PowerOff => "poweroff",
+            ShutdownKind::Reboot => "reboot",
+        }
+    }
+    fn from_logind(kind: &str) -> Option<Self> {
+        match kind {
+            "poweroff" | "dry-poweroff" => Some(ShutdownKind::PowerOff),
+            "reboot" | "dry-reboot" => Some(ShutdownKind::Reboot),
+            _ => None,
+        }
+    }
+    pub fn as_localized_string(self) -> String {
+        match self {
+            ShutdownKind::PowerOff => fl!("schedule-shutdown"),
+            ShutdownKind::Reboot => fl!("schedule-restart"),
+        }
+    }
+}
+
+// A pending shutdown, whether set by us or by anyone else
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduledShutdown {
+    pub kind: ShutdownKind,
+    pub at: SystemTime,
+}
+impl ScheduledShutdown {
+    fn from_logind((kind, usec): (String, u64)) -> Option<Self> {
+        if usec == 0 {
+            return None;
+        }
+        Some(ScheduledShutdown {
+            kind: ShutdownKind::from_logind(&kind)?,
+            at: UNIX_EPOCH + Duration::from_micros(usec),
+        })
+    }
+    pub fn remaining(&self) -> Duration {
+        self.at
+            .duration_since(SystemTime::now())
+            .unwrap_or_default()
+    }
+    // Short countdown for the panel badge, e.g. "1h 5m"
+    pub fn remaining_short(&self) -> String {
+        let minutes = self.remaining().as_secs().div_ceil(60);
+        if minutes >= 60 {
+            format!("{}h {}m", minutes / 60, minutes % 60)
+        } else {
+            format!("{}m", minutes)
+        }
+    }
+    pub fn as_localized_string(&self) -> String {
+        let time = self.remaining_short();
+        match self.kind {
+            ShutdownKind::PowerOff => fl!("shutdown-scheduled", time = time),
+            ShutdownKind::Reboot => fl!("restart-scheduled", time = time),
+        }
+    }
+}
+
+// A week is as far ahead as the custom delay goes
+pub const MAX_SCHEDULE_MINUTES: u64 = 7 * 24 * 60;
+
+pub async fn schedule_shutdown(
+    kind: ShutdownKind,
+    minutes: u64,
+) -> zbus::Result<Option<ScheduledShutdown>> {
+    let out_of_range = || zbus::Error::Failure(format!("can't schedule {} minutes ahead", minutes));
+    let delay = minutes
+        .checked_mul(60)
+        .map(Duration::from_secs)
+        .ok_or_else(out_of_range)?;
+    let at = SystemTime::now()
+        .checked_add(delay)
+        .ok_or_else(out_of_range)?;
+    let usec = at
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as u64;
+    let connection = Connection::system().await?;
+    let manager_proxy = LogindManagerProxy::new(&connection).await?;
+    manager_proxy
+        .schedule_shutdown(kind.as_logind(), usec)
+        .await?;
+    Ok(Some(ScheduledShutdown { kind, at }))
+}
+pub async fn cancel_scheduled_shutdown() -> zbus::Result<Option<ScheduledShutdown>> {
+    let connection = Connection::system().await?;
+    let manager_proxy = LogindManagerProxy::new(&connection).await?;
+    if manager_proxy.cancel_scheduled_shutdown().await? {
+        Ok(None)
+    } else {
+        Err(zbus::Error::Failure(
+            "no scheduled shutdown to cancel".to_string(),
+        ))
+    }
+}
+
+// What logind has scheduled right now, set by us or by anyone else
+pub async fn scheduled_shutdown() -> zbus::Result<Option<ScheduledShutdown>> {
+    let connection = Connection::system().await?;
+    let manager_proxy = LogindManagerProxy::new(&connection).await?;
+    let current = manager_proxy.scheduled_shutdown().await?;
+    Ok(ScheduledShutdown::from_logind(current))
+}
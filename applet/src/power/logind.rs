@@ -0,0 +1,22 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use zbus::proxy;
+
+#[proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+trait LogindManager {
+    // Each inhibitor is (what, who, why, mode, uid, pid)
+    fn list_inhibitors(&self) -> zbus::Result<Vec<(String, String, String, String, u32, u32)>>;
+
+    // Type is "poweroff" or "reboot", time is absolute CLOCK_REALTIME in microseconds
+    fn schedule_shutdown(&self, type_: &str, usec: u64) -> zbus::Result<()>;
+
+    fn cancel_scheduled_shutdown(&self) -> zbus::Result<bool>;
+
+    // Changes aren't signalled, so this has to be read each time
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn scheduled_shutdown(&self) -> zbus::Result<(String, u64)>;
+}
@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: GPL-3.0-only
+//
+// Power profile switching through power-profiles-daemon
+
+use cosmic::iced::futures::future::{self, Either};
+use cosmic::iced::futures::{SinkExt, Stream, StreamExt, channel::mpsc::Sender, stream::select};
+use cosmic::iced::{Subscription, stream};
+use std::collections::HashMap;
+use zbus::fdo::DBusProxy;
+use zbus::zvariant::OwnedValue;
+use zbus::{Connection, Proxy};
+
+// (service, path, interface), newest name first
+const SERVICES: [(&str, &str, &str); 2] = [
+    (
+        "org.freedesktop.UPower.PowerProfiles",
+        "/org/freedesktop/UPower/PowerProfiles",
+        "org.freedesktop.UPower.PowerProfiles",
+    ),
+    (
+        "net.hadess.PowerProfiles",
+        "/net/hadess/PowerProfiles",
+        "net.hadess.PowerProfiles",
+    ),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PowerProfiles {
+    pub active: String,
+    pub available: Vec<String>,
+}
+
+pub async fn set_active(profile: String) -> zbus::Result<()> {
+    let connection = Connection::system().await?;
+    let proxy = find_proxy(&connection).await?;
+    proxy.set_property("ActiveProfile", profile).await?;
+    Ok(())
+}
+
+// Emits the current state on start and whenever either property changes,
+// or None while no power-profiles-daemon is available
+pub fn subscription() -> Subscription<Option<PowerProfiles>> {
+    Subscription::run_with_id(
+        "logomenu-power-profiles",
+        stream::channel(4, |mut output| async move {
+            if let Err(e) = watch(&mut output).await {
+                eprintln!("cosmic-ext-applet-logomenu ERROR: '{}'", e);
+                let _ = output.send(None).await;
+            }
+            std::future::pending::<()>().await;
+        }),
+    )
+}
+
+async fn watch(output: &mut Sender<Option<PowerProfiles>>) -> zbus::Result<()> {
+    let connection = Connection::system().await?;
+    let dbus = DBusProxy::new(&connection).await?;
+    // the daemon starting, stopping or restarting changes who owns its names,
+    // and each time the service is looked up again
+    let mut owner_changes = select(
+        dbus.receive_name_owner_changed_with_args(&[(0, SERVICES[0].0)])
+            .await?
+            .map(|_| ()),
+        dbus.receive_name_owner_changed_with_args(&[(0, SERVICES[1].0)])
+            .await?
+            .map(|_| ()),
+    );
+
+    loop {
+        let followed = match find_proxy(&connection).await {
+            Ok(proxy) => follow(&proxy, output, &mut owner_changes).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = followed {
+            eprintln!("cosmic-ext-applet-logomenu ERROR: '{}'", e);
+            let _ = output.send(None).await;
+            if owner_changes.next().await.is_none() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+// Sends the daemon's state until the owner of one of its names changes
+async fn follow(
+    proxy: &Proxy<'static>,
+    output: &mut Sender<Option<PowerProfiles>>,
+    owner_changes: &mut (impl Stream<Item = ()> + Unpin),
+) -> zbus::Result<()> {
+    let active_changes = proxy
+        .receive_property_changed::<String>("ActiveProfile")
+        .await
+        .map(|_| ());
+    let profiles_changes = proxy
+        .receive_property_changed::<Vec<HashMap<String, OwnedValue>>>("Profiles")
+        .await
+        .map(|_| ());
+    let mut changes = select(active_changes, profiles_changes);
+
+    let _ = output.send(Some(read(proxy).await?)).await;
+    loop {
+        match future::select(changes.next(), owner_changes.next()).await {
+            Either::Left((Some(()), _)) => {
+                let _ = output.send(Some(read(proxy).await?)).await;
+            }
+            _ => return Ok(()),
+        }
+    }
+}
+
+async fn find_proxy(connection: &Connection) -> zbus::Result<Proxy<'static>> {
+    let mut last_error = zbus::Error::Failure("no power profiles service".to_string());
+    for (service, path, interface) in SERVICES {
+        let proxy = Proxy::new(connection, service, path, interface).await?;
+        match proxy.get_property::<String>("ActiveProfile").await {
+            Ok(_) => return Ok(proxy),
+            Err(e) => last_error = e.into(),
+        }
+    }
+    Err(last_error)
+}
+
+async fn read(proxy: &Proxy<'_>) -> zbus::Result<PowerProfiles> {
+    let active = proxy.get_property::<String>("ActiveProfile").await?;
+    let available = proxy
+        .get_property::<Vec<HashMap<String, OwnedValue>>>("Profiles")
+        .await?
+        .iter()
+        .filter_map(|profile| {
+            profile
+                .get("Profile")
+                .and_then(|value| <&str>::try_from(&**value).ok())
+                .map(str::to_owned)
+        })
+        .collect();
+    Ok(PowerProfiles { active, available })
+}
@@ -0,0 +1,177 @@
+// SPDX-License-Identifier: GPL-3.0-only
+//
+// Reaps launched processes and records how they finished
+
+use crate::launch;
+use cosmic::iced::futures::channel::oneshot;
+use cosmic::iced::futures::future;
+use liblog::{LaunchRecord, flatpak};
+use rustix::pipe::{PipeFlags, pipe_with};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, ErrorKind, Read, Write};
+use std::process::Child;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const STDERR_LINES: usize = 10;
+// Only the end of a chatty app's output is kept
+const STDERR_TAIL_BYTES: usize = 64 * 1024;
+// Output written just before exiting may still be in the pipe
+const STDERR_DRAIN_TIMEOUT: Duration = Duration::from_millis(200);
+
+// Where a launch's stderr goes. Everything read from the pipe is passed on to
+// the applet's own stderr, and so the journal, with the end kept for the
+// launch log.
+pub struct StderrLog {
+    tail: Arc<Mutex<Tail>>,
+    closed: oneshot::Receiver<()>,
+}
+impl StderrLog {
+    pub fn create() -> io::Result<(Self, File)> {
+        let (reader, writer) = pipe_with(PipeFlags::CLOEXEC)?;
+        let tail = Arc::new(Mutex::new(Tail::default()));
+        let (tx, closed) = oneshot::channel();
+
+        let shared = tail.clone();
+        thread::spawn(move || {
+            forward(File::from(reader), &shared);
+            let _ = tx.send(());
+        });
+        Ok((StderrLog { tail, closed }, File::from(writer)))
+    }
+
+    // The last few lines written. Anything the app left running keeps
+    // writing to the journal after this.
+    async fn tail(self) -> Vec<String> {
+        let _ = future::select(self.closed, launch::timer(STDERR_DRAIN_TIMEOUT)).await;
+        self.tail
+            .lock()
+            .map(|tail| tail.lines())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Default)]
+struct Tail {
+    bytes: VecDeque<u8>,
+    truncated: bool,
+}
+impl Tail {
+    fn push(&mut self, bytes: &[u8]) {
+        self.bytes.extend(bytes);
+        let excess = self.bytes.len().saturating_sub(STDERR_TAIL_BYTES);
+        if excess > 0 {
+            self.bytes.drain(..excess);
+            self.truncated = true;
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        let bytes: Vec<u8> = self.bytes.iter().copied().collect();
+        let text = String::from_utf8_lossy(&bytes);
+        let mut lines: Vec<String> = text.lines().map(str::to_owned).collect();
+        // the first line is likely cut short once the start was dropped
+        if self.truncated && lines.len() > 1 {
+            lines.remove(0);
+        }
+        let excess = lines.len().saturating_sub(STDERR_LINES);
+        lines.drain(..excess);
+        lines
+    }
+}
+
+// Copy a launch's stderr to ours until every process holding it has exited
+fn forward(mut pipe: File, tail: &Mutex<Tail>) {
+    let mut buf = [0; 4096];
+    loop {
+        match pipe.read(&mut buf) {
+            Ok(0) => return,
+            Ok(n) => {
+                let _ = io::stderr().write_all(&buf[..n]);
+                if let Ok(mut tail) = tail.lock() {
+                    tail.push(&buf[..n]);
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => {
+                eprintln!("cosmic-ext-applet-logomenu ERROR: '{}'", e);
+                return;
+            }
+        }
+    }
+}
+
+// Wait for a child to exit on its own thread, so a long-lived app never holds
+// up the applet, then keep the tail of its stderr
+pub async fn supervise(
+    label: String,
+    command: String,
+    mut child: Child,
+    stderr: StderrLog,
+) -> LaunchRecord {
+    let launch = Launch::start(label, command);
+    let (tx, rx) = oneshot::channel();
+
+    thread::spawn(move || {
+        let exit_code = match child.wait() {
+            Ok(status) => status.code(),
+            Err(e) => {
+                eprintln!("cosmic-ext-applet-logomenu ERROR: '{}'", e);
+                None
+            }
+        };
+        let _ = tx.send(exit_code);
+    });
+
+    let exit_code = rx.await.unwrap_or(None);
+    launch.finish(exit_code, stderr).await
+}
+
+// Run a shell command on the host from inside the Flatpak sandbox and wait for it
+pub async fn supervise_host(label: String, command: String) -> zbus::Result<LaunchRecord> {
+    let (stderr, stderr_file) =
+        StderrLog::create().map_err(|e| zbus::Error::InputOutput(e.into()))?;
+    let output = flatpak::Output {
+        stdout: None,
+        stderr: Some(stderr_file),
+    };
+    let mut child = flatpak::spawn(&["sh", "-c", &command], output, 0).await?;
+    let launch = Launch::start(label, command);
+
+    let exit_code = child.wait().await?;
+    Ok(launch.finish(exit_code, stderr).await)
+}
+
+struct Launch {
+    label: String,
+    command: String,
+    started: u64,
+    start: Instant,
+}
+impl Launch {
+    fn start(label: String, command: String) -> Self {
+        Launch {
+            label,
+            command,
+            started: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            start: Instant::now(),
+        }
+    }
+
+    async fn finish(self, exit_code: Option<i32>, stderr: StderrLog) -> LaunchRecord {
+        let duration_ms = self.start.elapsed().as_millis() as u64;
+        LaunchRecord {
+            label: self.label,
+            command: self.command,
+            started: self.started,
+            duration_ms,
+            exit_code,
+            stderr: stderr.tail().await,
+        }
+    }
+}
@@ -0,0 +1 @@
+liblog.ftl
\ No newline at end of file
@@ -1,4 +1,4 @@
-## The files liblog.ftl and cosmic_logomenu_settings.ftl are hard linked.
+## The files liblog.ftl, cosmic_ext_logomenu_settings.ftl and cosmic_ext_applet_logomenu.ftl are linked.
 ## Please copy the whole directory when creating new languages to maintain this
 ## But you will only need to edit one
 
@@ -38,6 +38,8 @@ custom-command = Custom command
 default-app = Default app
 power-action = Power action
 divider = Divider
+header = Header
+submenu = Submenu
 
 # Default app categories
 web-browser = Web browser
@@ -65,3 +67,114 @@ logout = Logout
 suspend = Suspend
 restart = Restart
 shutdown = Shutdown
+
+# Power action inhibitors
+inhibited-title = { $action } is being held up by:
+inhibitor-delay = { $why } (delaying)
+proceed-anyway = Proceed anyway
+
+# Scheduled shutdown
+schedule-restart = Restart in…
+schedule-shutdown = Shut down in…
+in-15-minutes = 15 minutes
+in-1-hour = 1 hour
+custom-minutes = Minutes
+schedule = Schedule
+shutdown-scheduled = Shutting down in { $time }
+restart-scheduled = Restarting in { $time }
+cancel-scheduled-shutdown = Cancel scheduled shutdown
+
+# Power profiles
+power-profiles = Power profile
+power-saver = Power saver
+balanced = Balanced
+performance = Performance
+
+# Failure notifications
+action-failed = "{ $label }" failed
+notify-on-failure = Notify when a menu item fails
+
+# Launch resource limits
+memory-limit = Memory limit (MiB)
+cpu-weight = CPU weight
+nice = Nice
+unlimited = Unlimited
+
+# Launch log
+launch-log = Launch log
+launch-log-empty = Nothing has been launched from the menu yet
+exited-with-code = Exited with code { $code }
+exited-by-signal = Killed by a signal
+exited-successfully = Exited successfully
+launched-ago = { $time } ago
+clear = Clear
+
+# Undo and redo
+edit = Edit
+undo = Undo
+redo = Redo
+item-removed = Item removed
+menu-reset = Menu reset to default
+
+# Config conflicts
+config-conflict = Settings changed elsewhere
+config-conflict-body = The menu settings were changed outside this window since they were loaded. Keep your changes, or load the saved settings instead?
+keep-changes = Keep my changes
+load-saved = Load saved settings
+
+# Command validation
+command-invalid = This command is empty or has unmatched quotes
+command-not-found = { $program } isn't installed or isn't on your PATH
+command-not-executable = { $program } isn't executable
+did-you-mean = Did you mean
+
+# Application picker
+icon = Icon
+choose-application = Choose application…
+search-applications = Search applications
+loading = Loading…
+
+# Icon picker
+choose-icon = Choose…
+search-icons = Search icons
+symbolic-only = Symbolic icons only
+refine-search = Showing the first matches, search to narrow them down
+choose-file = Choose file…
+
+# Popup preview
+preview = Preview
+
+# Logo gallery
+search-logos = Search logos
+custom-logo = Custom logo
+
+# Selecting and grouping items
+select = Select
+select-all = Select all
+done = Done
+selected-count = { $count } selected
+enable = Enable
+disable = Disable
+group-submenu = Group into submenu
+group-header = Add header
+new-submenu = New submenu
+new-header = New header
+submenu-items = { $count ->
+    [one] 1 item
+   *[other] { $count } items
+}
+duplicate = Duplicate
+ungroup = Ungroup
+items-removed = { $count ->
+    [one] 1 item removed
+   *[other] { $count } items removed
+}
+
+# Keyboard shortcuts
+move-up = Move up
+move-down = Move down
+search = Search
+
+# Filtering the menu list
+filter-items = Filter by label, command or type
+no-matching-items = No items match the filter
@@ -1,4 +1,4 @@
-## The files liblog.ftl and cosmic_logomenu_settings.ftl are hard linked.
+## The files liblog.ftl, cosmic_ext_logomenu_settings.ftl and cosmic_ext_applet_logomenu.ftl are linked.
 ## Please copy the whole directory when creating new languages to maintain this
 ## But you will only need to edit one
 
@@ -0,0 +1,152 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Runs commands on the host from inside the Flatpak sandbox, through the
+//! HostCommand API that flatpak-spawn itself uses.
+
+use futures_util::StreamExt;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::fd::AsFd;
+use zbus::zvariant::Fd;
+use zbus::{Connection, proxy};
+
+/// Kill the command when the caller drops off the session bus.
+pub const WATCH_BUS: u32 = 1 << 1;
+
+const SIGTERM: u32 = 15;
+
+// Variables that mean the same thing on the host as in the sandbox. Anything
+// else, such as the bus addresses or search paths, points at the sandbox's
+// view of the filesystem and would break the host command.
+const HOST_ENV: &[&str] = &[
+    "LANG",
+    "LANGUAGE",
+    "DISPLAY",
+    "WAYLAND_DISPLAY",
+    "XDG_CURRENT_DESKTOP",
+    "XDG_SESSION_DESKTOP",
+    "XDG_SESSION_TYPE",
+];
+
+#[proxy(
+    interface = "org.freedesktop.Flatpak.Development",
+    default_service = "org.freedesktop.Flatpak",
+    default_path = "/org/freedesktop/Flatpak/Development"
+)]
+trait FlatpakDevelopment {
+    // Paths and arguments are NUL terminated byte strings
+    fn host_command(
+        &self,
+        cwd_path: &[u8],
+        argv: &[&[u8]],
+        fds: HashMap<u32, Fd<'_>>,
+        envs: HashMap<&str, &str>,
+        flags: u32,
+    ) -> zbus::Result<u32>;
+
+    fn host_command_signal(
+        &self,
+        pid: u32,
+        signal: u32,
+        to_process_group: bool,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn host_command_exited(&self, pid: u32, exit_status: u32) -> zbus::Result<()>;
+}
+
+/// Where the host command's output goes; anything left as `None` is discarded.
+#[derive(Debug, Default)]
+pub struct Output {
+    pub stdout: Option<File>,
+    pub stderr: Option<File>,
+}
+
+/// A process running on the host.
+pub struct HostChild {
+    pub pid: u32,
+    proxy: FlatpakDevelopmentProxy<'static>,
+    exited: HostCommandExitedStream<'static>,
+}
+impl HostChild {
+    /// Waits for the host process to exit, returning its exit code or `None`
+    /// if it was killed.
+    pub async fn wait(&mut self) -> zbus::Result<Option<i32>> {
+        while let Some(signal) = self.exited.next().await {
+            let Ok(args) = signal.args() else {
+                continue;
+            };
+            if args.pid == self.pid {
+                let status = args.exit_status;
+                return Ok((status & 0x7f == 0).then_some(((status >> 8) & 0xff) as i32));
+            }
+        }
+        Err(zbus::Error::Failure(
+            "lost track of host command".to_string(),
+        ))
+    }
+
+    /// Asks the host process and everything it started to stop.
+    pub async fn terminate(&self) -> zbus::Result<()> {
+        self.proxy
+            .host_command_signal(self.pid, SIGTERM, true)
+            .await
+    }
+}
+
+/// Starts a command on the host from the user's home directory, in the host
+/// session's environment with only the locale and display passed along.
+/// Unlike flatpak-spawn there is no wrapper process left behind.
+pub async fn spawn(argv: &[&str], output: Output, flags: u32) -> zbus::Result<HostChild> {
+    let connection = Connection::session().await?;
+    let proxy = FlatpakDevelopmentProxy::new(&connection).await?;
+
+    // Listen before starting, so a quick exit isn't missed
+    let exited = proxy.receive_host_command_exited().await?;
+
+    let cwd = nul_terminated(&std::env::var("HOME").unwrap_or_else(|_| String::from("/")));
+    let argv: Vec<Vec<u8>> = argv.iter().map(|arg| nul_terminated(arg)).collect();
+    let argv: Vec<&[u8]> = argv.iter().map(Vec::as_slice).collect();
+
+    let vars: Vec<(String, String)> = std::env::vars()
+        .filter(|(name, _)| HOST_ENV.contains(&name.as_str()) || name.starts_with("LC_"))
+        .collect();
+    let envs: HashMap<&str, &str> = vars
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .collect();
+
+    let stdin = File::open("/dev/null").map_err(io_error)?;
+    let null = OpenOptions::new()
+        .write(true)
+        .open("/dev/null")
+        .map_err(io_error)?;
+    let stdout = match output.stdout {
+        Some(stdout) => stdout,
+        None => null.try_clone().map_err(io_error)?,
+    };
+    let stderr = match output.stderr {
+        Some(stderr) => stderr,
+        None => null,
+    };
+    let fds = HashMap::from([
+        (0, Fd::from(stdin.as_fd())),
+        (1, Fd::from(stdout.as_fd())),
+        (2, Fd::from(stderr.as_fd())),
+    ]);
+
+    let pid = proxy.host_command(&cwd, &argv, fds, envs, flags).await?;
+
+    Ok(HostChild { pid, proxy, exited })
+}
+
+fn nul_terminated(value: &str) -> Vec<u8> {
+    let mut bytes = value.as_bytes().to_vec();
+    bytes.push(0);
+    bytes
+}
+
+fn io_error(e: io::Error) -> zbus::Error {
+    zbus::Error::InputOutput(e.into())
+}
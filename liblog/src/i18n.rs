@@ -38,6 +38,43 @@ pub static LANGUAGE_LOADER: LazyLock<FluentLanguageLoader> = LazyLock::new(|| {
     loader
 });
 
+/// Looks up a message by an ID only known at runtime, such as one read from the config.
+#[must_use]
+pub fn message(message_id: &str) -> Option<String> {
+    LANGUAGE_LOADER
+        .has(message_id)
+        .then(|| LANGUAGE_LOADER.get(message_id))
+}
+
+// One loader per bundled language, for recognising text saved in any of them
+static ALL_LANGUAGES: LazyLock<Vec<FluentLanguageLoader>> = LazyLock::new(|| {
+    let languages = LANGUAGE_LOADER
+        .available_languages(&Localizations)
+        .unwrap_or_default();
+
+    languages
+        .into_iter()
+        .filter_map(|language| {
+            let loader: FluentLanguageLoader = fluent_language_loader!();
+            match loader.load_languages(&Localizations, &[language]) {
+                Ok(()) => Some(loader),
+                Err(why) => {
+                    eprintln!("error while loading fluent localizations: {why}");
+                    None
+                }
+            }
+        })
+        .collect()
+});
+
+/// Whether `text` is the message in any of the bundled languages.
+#[must_use]
+pub fn is_translation_of(message_id: &str, text: &str) -> bool {
+    ALL_LANGUAGES
+        .iter()
+        .any(|loader| loader.has(message_id) && loader.get(message_id) == text)
+}
+
 /// Request a localized string by ID from the i18n/ directory.
 #[macro_export]
 macro_rules! fl {
@@ -4,18 +4,30 @@ use cosmic_config::{CosmicConfigEntry, cosmic_config_derive::CosmicConfigEntry};
 use phf::phf_map;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::ops::RangeInclusive;
 
+#[cfg(feature = "flatpak")]
+pub mod flatpak;
 pub mod i18n;
+pub mod sandbox;
+#[cfg(feature = "view")]
+pub mod view;
 pub use crate::i18n::init;
+pub use crate::sandbox::{is_flatpak, is_settings_command};
+
+pub const CONFIG_ID: &str = "dev.cappsy.CosmicExtAppletLogoMenu";
 
 // Config
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq, CosmicConfigEntry)]
 #[version = 1]
+// Fields left out of a file, such as one written by hand, keep their defaults
+#[serde(default)]
 pub struct LogoMenuConfig {
     pub logo: String,
     pub menu_items: MenuItems,
     pub custom_logo_active: bool,
     pub custom_logo_path: String,
+    pub notify_on_failure: bool,
 }
 impl Default for LogoMenuConfig {
     fn default() -> Self {
@@ -24,23 +36,67 @@ impl Default for LogoMenuConfig {
             menu_items: MenuItems::default(),
             custom_logo_active: false,
             custom_logo_path: String::from(""),
+            notify_on_failure: true,
+        }
+    }
+}
+
+impl LogoMenuConfig {
+    pub fn config() -> Result<cosmic_config::Config, cosmic_config::Error> {
+        cosmic_config::Config::new(CONFIG_ID, Self::VERSION)
+    }
+    // Read the config, falling back to defaults for anything missing or invalid
+    pub fn load() -> Self {
+        Self::config()
+            .map(|config| Self::get_entry(&config).unwrap_or_else(|(_errors, config)| config))
+            .unwrap_or_default()
+    }
+}
+
+// Problems editing the menu
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MenuError {
+    NoSuchItem(usize),
+    MissingLabel,
+    MissingCommand,
+    UnknownPowerAction(String),
+}
+impl fmt::Display for MenuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MenuError::NoSuchItem(i) => write!(f, "there is no item at position {}", i),
+            MenuError::MissingLabel => write!(f, "the item needs a label"),
+            MenuError::MissingCommand => write!(f, "the item needs a command"),
+            MenuError::UnknownPowerAction(command) => {
+                write!(f, "'{}' is not a known power action", command)
+            }
         }
     }
 }
+impl std::error::Error for MenuError {}
 
 // Menu item types
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Copy)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Default)]
 pub enum MenuItemType {
+    #[default]
     LaunchAction,
     PowerAction,
+    PowerProfiles,
     Divider,
+    // a heading over the items that follow it
+    Header,
+    // items folded away under one entry, held in `children`
+    Submenu,
 }
 impl MenuItemType {
     pub fn as_localized_string(&self) -> String {
         match self {
             MenuItemType::LaunchAction => fl!("launch-action"),
             MenuItemType::PowerAction => fl!("power-action"),
+            MenuItemType::PowerProfiles => fl!("power-profiles"),
             MenuItemType::Divider => fl!("divider"),
+            MenuItemType::Header => fl!("header"),
+            MenuItemType::Submenu => fl!("submenu"),
         }
     }
 }
@@ -53,6 +109,8 @@ pub enum PowerActionOption {
     Suspend,
     Restart,
     Shutdown,
+    ScheduleRestart,
+    ScheduleShutdown,
 }
 // localised option strings
 impl PowerActionOption {
@@ -63,8 +121,23 @@ impl PowerActionOption {
             PowerActionOption::Suspend => "Suspend".to_owned(),
             PowerActionOption::Restart => "Restart".to_owned(),
             PowerActionOption::Shutdown => "Shutdown".to_owned(),
+            PowerActionOption::ScheduleRestart => "ScheduleRestart".to_owned(),
+            PowerActionOption::ScheduleShutdown => "ScheduleShutdown".to_owned(),
         }
     }
+    pub fn from_command(command: &str) -> Option<Self> {
+        [
+            PowerActionOption::Lock,
+            PowerActionOption::Logout,
+            PowerActionOption::Suspend,
+            PowerActionOption::Restart,
+            PowerActionOption::Shutdown,
+            PowerActionOption::ScheduleRestart,
+            PowerActionOption::ScheduleShutdown,
+        ]
+        .into_iter()
+        .find(|option| option.command() == command)
+    }
     pub fn as_localized_string(&self) -> String {
         match self {
             PowerActionOption::Lock => fl!("lock"),
@@ -72,34 +145,215 @@ impl PowerActionOption {
             PowerActionOption::Suspend => fl!("suspend"),
             PowerActionOption::Restart => fl!("restart"),
             PowerActionOption::Shutdown => fl!("shutdown"),
+            PowerActionOption::ScheduleRestart => fl!("schedule-restart"),
+            PowerActionOption::ScheduleShutdown => fl!("schedule-shutdown"),
         }
     }
 }
 
+// Optional systemd resource controls for launched items
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ResourceLimits {
+    // MemoryMax in bytes
+    pub memory_max: Option<u64>,
+    pub cpu_weight: Option<u64>,
+    pub nice: Option<i32>,
+}
+impl ResourceLimits {
+    // What systemd and the kernel accept
+    pub const CPU_WEIGHT: RangeInclusive<u64> = 1..=10000;
+    // Raising priority takes privileges the applet doesn't have
+    pub const NICE: RangeInclusive<i32> = 0..=19;
+
+    pub fn is_empty(&self) -> bool {
+        self == &ResourceLimits::default()
+    }
+}
+
 // Individual menu item struct
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct MenuItem {
     pub item_type: MenuItemType,
     pub label: Option<String>,
+    // Fluent message ID shown in place of `label`, so built in items follow
+    // the session language; `label` is kept as the fallback
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label_key: Option<String>,
     pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "ResourceLimits::is_empty")]
+    pub resources: ResourceLimits,
+    // icon theme name, or a path to an image file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    // disabled items stay in the config but are left out of the menu
+    #[serde(default = "enabled_by_default", skip_serializing_if = "is_enabled")]
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<MenuItem>,
+}
+impl Default for MenuItem {
+    fn default() -> Self {
+        Self {
+            item_type: MenuItemType::default(),
+            label: None,
+            label_key: None,
+            command: None,
+            resources: ResourceLimits::default(),
+            icon: None,
+            enabled: true,
+            children: Vec::new(),
+        }
+    }
 }
 impl MenuItem {
     pub fn item_type(&self) -> MenuItemType {
         self.item_type.clone()
     }
+    // Resolved each time it's asked for, so a language change shows up on the next draw
     pub fn label(&self) -> Option<String> {
-        self.label.clone()
+        self.label_key
+            .as_deref()
+            .and_then(i18n::message)
+            .or_else(|| self.label.clone())
+    }
+    // Literal text typed in by the user, replacing any message ID
+    pub fn set_label(&mut self, label: String) {
+        self.label = Some(label);
+        self.label_key = None;
     }
     pub fn command(&self) -> Option<String> {
         self.command.clone()
     }
+    pub fn resources(&self) -> ResourceLimits {
+        self.resources.clone()
+    }
+    pub fn icon(&self) -> Option<String> {
+        self.icon.clone().filter(|icon| !icon.is_empty())
+    }
+    pub fn validate(&self) -> Result<(), MenuError> {
+        let has_label = self.label().is_some_and(|l| !l.trim().is_empty());
+        let command = self.command.clone().unwrap_or_default();
+        match self.item_type {
+            MenuItemType::LaunchAction if !has_label => Err(MenuError::MissingLabel),
+            MenuItemType::LaunchAction if command.trim().is_empty() => {
+                Err(MenuError::MissingCommand)
+            }
+            MenuItemType::PowerAction if !has_label => Err(MenuError::MissingLabel),
+            MenuItemType::PowerAction if PowerActionOption::from_command(&command).is_none() => {
+                Err(MenuError::UnknownPowerAction(command))
+            }
+            MenuItemType::Header | MenuItemType::Submenu if !has_label => {
+                Err(MenuError::MissingLabel)
+            }
+            MenuItemType::Submenu => self.children.iter().try_for_each(MenuItem::validate),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+fn is_enabled(enabled: &bool) -> bool {
+    *enabled
+}
+
+// Launch history written by the applet to its state config and shown in settings
+pub const LAUNCH_LOG_KEY: &str = "launch_log";
+pub const LAUNCH_LOG_LIMIT: usize = 50;
+
+// One finished launch of a menu item
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LaunchRecord {
+    pub label: String,
+    pub command: String,
+    // Seconds since the Unix epoch
+    pub started: u64,
+    pub duration_ms: u64,
+    // None if the process was killed by a signal
+    pub exit_code: Option<i32>,
+    // Last few lines written to stderr
+    pub stderr: Vec<String>,
+}
+impl LaunchRecord {
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
 }
 
 // Top level menu items struct
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(from = "StoredMenuItems")]
 pub struct MenuItems {
     pub items: Vec<MenuItem>,
 }
+
+// The menu as written to the config, before older labels are brought up to date
+#[derive(Deserialize)]
+struct StoredMenuItems {
+    items: Vec<MenuItem>,
+}
+impl From<StoredMenuItems> for MenuItems {
+    fn from(stored: StoredMenuItems) -> Self {
+        let mut items = stored.items;
+        add_label_keys(&mut items);
+        MenuItems { items }
+    }
+}
+
+// Menus saved before labels could be message IDs have the built in labels
+// written out in whatever language was in use then. Any item still running a
+// default's command under that default's label, in any language, gets the
+// default's message ID back so it follows the session language again.
+fn add_label_keys(items: &mut [MenuItem]) {
+    let defaults = MenuItems::default().items;
+    for item in items.iter_mut().filter(|item| item.label_key.is_none()) {
+        let Some(label) = item.label.as_deref() else {
+            continue;
+        };
+        let key = defaults
+            .iter()
+            .filter(|default| {
+                default.item_type == item.item_type && default.command == item.command
+            })
+            .filter_map(|default| default.label_key.as_deref())
+            .find(|key| i18n::is_translation_of(key, label));
+        if let Some(key) = key {
+            item.label_key = Some(key.to_owned());
+        }
+    }
+}
+
+impl MenuItems {
+    // Insert a valid item at a position, or at the end, returning where it went
+    pub fn insert(&mut self, at: Option<usize>, item: MenuItem) -> Result<usize, MenuError> {
+        item.validate()?;
+        let at = at.unwrap_or(self.items.len());
+        if at > self.items.len() {
+            return Err(MenuError::NoSuchItem(at));
+        }
+        self.items.insert(at, item);
+        Ok(at)
+    }
+    pub fn remove(&mut self, i: usize) -> Result<MenuItem, MenuError> {
+        if i >= self.items.len() {
+            return Err(MenuError::NoSuchItem(i));
+        }
+        Ok(self.items.remove(i))
+    }
+    // Move an item so it ends up at position `to`
+    pub fn move_item(&mut self, from: usize, to: usize) -> Result<(), MenuError> {
+        if from >= self.items.len() {
+            return Err(MenuError::NoSuchItem(from));
+        }
+        if to >= self.items.len() {
+            return Err(MenuError::NoSuchItem(to));
+        }
+        let item = self.items.remove(from);
+        self.items.insert(to, item);
+        Ok(())
+    }
+}
 impl fmt::Display for MenuItems {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", self)
@@ -112,97 +366,130 @@ impl Default for MenuItems {
                 MenuItem {
                     item_type: MenuItemType::LaunchAction,
                     label: Some(fl!("about-system")),
+                    label_key: Some(String::from("about-system")),
                     command: Some(String::from("cosmic-settings about")),
+                    ..Default::default()
                 },
                 MenuItem {
                     item_type: MenuItemType::Divider,
                     label: None,
                     command: None,
+                    ..Default::default()
                 },
                 MenuItem {
                     item_type: MenuItemType::LaunchAction,
                     label: Some(fl!("applications")),
+                    label_key: Some(String::from("applications")),
                     command: Some(String::from("cosmic-app-library")),
+                    ..Default::default()
                 },
                 MenuItem {
                     item_type: MenuItemType::LaunchAction,
                     label: Some(fl!("launcher")),
+                    label_key: Some(String::from("launcher")),
                     command: Some(String::from("cosmic-launcher")),
+                    ..Default::default()
                 },
                 MenuItem {
                     item_type: MenuItemType::LaunchAction,
                     label: Some(fl!("workspaces")),
+                    label_key: Some(String::from("workspaces")),
                     command: Some(String::from("cosmic-workspaces")),
+                    ..Default::default()
                 },
                 MenuItem {
                     item_type: MenuItemType::Divider,
                     label: None,
                     command: None,
+                    ..Default::default()
                 },
                 MenuItem {
                     item_type: MenuItemType::LaunchAction,
                     label: Some(fl!("terminal")),
+                    label_key: Some(String::from("terminal")),
                     command: Some(String::from("cosmic-term")),
+                    ..Default::default()
                 },
                 MenuItem {
                     item_type: MenuItemType::LaunchAction,
                     label: Some(fl!("files")),
+                    label_key: Some(String::from("files")),
                     command: Some(String::from("cosmic-files")),
+                    ..Default::default()
                 },
                 MenuItem {
                     item_type: MenuItemType::LaunchAction,
                     label: Some(fl!("software")),
+                    label_key: Some(String::from("software")),
                     command: Some(String::from("cosmic-store")),
+                    ..Default::default()
                 },
                 MenuItem {
                     item_type: MenuItemType::LaunchAction,
                     label: Some(fl!("settings")),
+                    label_key: Some(String::from("settings")),
                     command: Some(String::from("cosmic-settings")),
+                    ..Default::default()
                 },
                 MenuItem {
                     item_type: MenuItemType::Divider,
                     label: None,
                     command: None,
+                    ..Default::default()
                 },
                 MenuItem {
                     item_type: MenuItemType::PowerAction,
                     label: Some(fl!("lock")),
+                    label_key: Some(String::from("lock")),
                     command: Some(String::from("Lock")),
+                    ..Default::default()
                 },
                 MenuItem {
                     item_type: MenuItemType::PowerAction,
                     label: Some(fl!("logout")),
+                    label_key: Some(String::from("logout")),
                     command: Some(String::from("Logout")),
+                    ..Default::default()
                 },
                 MenuItem {
                     item_type: MenuItemType::PowerAction,
                     label: Some(fl!("suspend")),
+                    label_key: Some(String::from("suspend")),
                     command: Some(String::from("Suspend")),
+                    ..Default::default()
                 },
                 MenuItem {
                     item_type: MenuItemType::Divider,
                     label: None,
                     command: None,
+                    ..Default::default()
                 },
                 MenuItem {
                     item_type: MenuItemType::PowerAction,
                     label: Some(fl!("restart")),
+                    label_key: Some(String::from("restart")),
                     command: Some(String::from("Restart")),
+                    ..Default::default()
                 },
                 MenuItem {
                     item_type: MenuItemType::PowerAction,
                     label: Some(fl!("shutdown")),
+                    label_key: Some(String::from("shutdown")),
                     command: Some(String::from("Shutdown")),
+                    ..Default::default()
                 },
                 MenuItem {
                     item_type: MenuItemType::Divider,
                     label: None,
                     command: None,
+                    ..Default::default()
                 },
                 MenuItem {
                     item_type: MenuItemType::LaunchAction,
-                    label: Some(fl!("menu-settings").to_string()),
+                    label: Some(fl!("menu-settings")),
+                    label_key: Some(String::from("menu-settings")),
                     command: Some(String::from("cosmic-ext-logomenu-settings")),
+                    ..Default::default()
                 },
             ],
         }
@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Detects whether we are running inside a Flatpak sandbox.
+
+use std::path::Path;
+use std::sync::LazyLock;
+
+/// Set to `1` or `0` to force sandbox detection on or off, e.g. for testing.
+pub const FLATPAK_OVERRIDE_ENV: &str = "COSMIC_LOGOMENU_FLATPAK";
+
+static IS_FLATPAK: LazyLock<bool> = LazyLock::new(|| {
+    match std::env::var(FLATPAK_OVERRIDE_ENV).as_deref() {
+        Ok("1") | Ok("true") => return true,
+        Ok("0") | Ok("false") => return false,
+        _ => {}
+    }
+
+    Path::new("/.flatpak-info").exists() || std::env::var_os("FLATPAK_ID").is_some()
+});
+
+/// Whether menu commands need to be sent out to the host.
+pub fn is_flatpak() -> bool {
+    *IS_FLATPAK
+}
+
+/// Commands that name the settings app, which ships alongside the applet and so
+/// always runs inside the sandbox rather than on the host.
+pub fn is_settings_command(command: &str) -> bool {
+    matches!(
+        command.trim(),
+        "cosmic-ext-logomenu-settings" | "cosmic-logomenu-settings"
+    )
+}
@@ -0,0 +1,250 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! The popup menu, drawn the same way by the applet and the settings preview.
+
+use crate::{MenuItem, MenuItemType, fl};
+use cosmic::Element;
+use cosmic::applet::{menu_button, padded_control};
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget::{self, Column};
+use std::path::{Path, PathBuf};
+
+/// Width limits of the popup window.
+pub const POPUP_MIN_WIDTH: f32 = 300.0;
+pub const POPUP_MAX_WIDTH: f32 = 372.0;
+
+/// The power profiles power-profiles-daemon offers, as shown in the menu.
+pub struct PowerProfilesView<'a> {
+    pub active: &'a str,
+    pub available: &'a [String],
+    pub expanded: bool,
+}
+
+/// Messages sent when parts of the menu are pressed.
+///
+/// Items inside a submenu are reported with the top-level submenu's index.
+/// Submenus are toggled by their path, the index at each level down to them.
+pub struct MenuMessages<Item, Profile, Submenu, Message>
+where
+    Item: Fn(usize, &MenuItem) -> Option<Message>,
+    Profile: Fn(&str) -> Message,
+    Submenu: Fn(Vec<usize>) -> Message,
+{
+    pub item: Item,
+    pub toggle_profiles: Message,
+    pub set_profile: Profile,
+    pub toggle_submenu: Submenu,
+}
+
+/// The empty popup the menu is pushed into.
+pub fn popup_column<'a, Message: 'static>() -> Column<'a, Message> {
+    widget::column().padding([8, 0]).spacing(0)
+}
+
+pub fn divider<'a, Message: 'static>() -> Element<'a, Message> {
+    let space_xxs = cosmic::theme::active().cosmic().spacing.space_xxs;
+    let space_s = cosmic::theme::active().cosmic().spacing.space_s;
+
+    padded_control(widget::divider::horizontal::default())
+        .padding([space_xxs, space_s])
+        .into()
+}
+
+/// Adds the configured menu items to the popup, skipping disabled ones.
+///
+/// Power profile items are left out when `power_profiles` is `None`, as the
+/// applet does when power-profiles-daemon isn't running. Submenus are only
+/// opened when their path is in `expanded_submenus`.
+pub fn menu_items<'a, Message, Item, Profile, Submenu>(
+    mut content_list: Column<'a, Message>,
+    items: &[MenuItem],
+    power_profiles: Option<PowerProfilesView<'_>>,
+    expanded_submenus: &[Vec<usize>],
+    messages: MenuMessages<Item, Profile, Submenu, Message>,
+) -> Column<'a, Message>
+where
+    Message: Clone + 'static,
+    Item: Fn(usize, &MenuItem) -> Option<Message>,
+    Profile: Fn(&str) -> Message,
+    Submenu: Fn(Vec<usize>) -> Message,
+{
+    for (i, item) in items.iter().enumerate() {
+        content_list = push_item(
+            content_list,
+            i,
+            &[i],
+            item,
+            &power_profiles,
+            expanded_submenus,
+            &messages,
+        );
+    }
+
+    content_list
+}
+
+// Submenu children are drawn one level further in, under their top-level index `i`
+fn push_item<'a, Message, Item, Profile, Submenu>(
+    mut content_list: Column<'a, Message>,
+    i: usize,
+    path: &[usize],
+    item: &MenuItem,
+    power_profiles: &Option<PowerProfilesView<'_>>,
+    expanded_submenus: &[Vec<usize>],
+    messages: &MenuMessages<Item, Profile, Submenu, Message>,
+) -> Column<'a, Message>
+where
+    Message: Clone + 'static,
+    Item: Fn(usize, &MenuItem) -> Option<Message>,
+    Profile: Fn(&str) -> Message,
+    Submenu: Fn(Vec<usize>) -> Message,
+{
+    let space_xxs = cosmic::theme::active().cosmic().spacing.space_xxs;
+    let space_s = cosmic::theme::active().cosmic().spacing.space_s;
+    let indent = (path.len() as u16 - 1) * space_s;
+
+    if !item.enabled {
+        return content_list;
+    }
+    match item.item_type() {
+        MenuItemType::LaunchAction | MenuItemType::PowerAction => {
+            content_list = content_list.push(
+                menu_button(
+                    widget::container(label(item, item.label().unwrap_or_default()))
+                        .padding([0, 0, 0, indent]),
+                )
+                .on_press_maybe((messages.item)(i, item)),
+            )
+        }
+        MenuItemType::Header => {
+            content_list =
+                content_list.push(
+                    padded_control(widget::text::heading(item.label().unwrap_or_default()))
+                        .padding([space_xxs, space_s, space_xxs, space_s + indent]),
+                )
+        }
+        MenuItemType::Submenu => {
+            let expanded = expanded_submenus.iter().any(|open| open == path);
+            content_list = content_list.push(
+                menu_button(
+                    widget::row()
+                        .align_y(Alignment::Center)
+                        .spacing(space_xxs)
+                        .padding([0, 0, 0, indent])
+                        .push(
+                            widget::container(label(item, item.label().unwrap_or_default()))
+                                .width(Length::Fill),
+                        )
+                        .push(
+                            widget::icon::from_name(if expanded {
+                                "go-down-symbolic"
+                            } else {
+                                "go-next-symbolic"
+                            })
+                            .size(16),
+                        ),
+                )
+                .on_press((messages.toggle_submenu)(path.to_vec())),
+            );
+            if expanded {
+                for (j, child) in item.children.iter().enumerate() {
+                    content_list = push_item(
+                        content_list,
+                        i,
+                        &[path, &[j]].concat(),
+                        child,
+                        power_profiles,
+                        expanded_submenus,
+                        messages,
+                    );
+                }
+            }
+        }
+        MenuItemType::PowerProfiles => {
+            let Some(profiles) = power_profiles else {
+                return content_list;
+            };
+            content_list = content_list.push(
+                menu_button(
+                    widget::row()
+                        .align_y(Alignment::Center)
+                        .spacing(space_xxs)
+                        .push(
+                            widget::container(label(
+                                item,
+                                item.label().unwrap_or_else(|| fl!("power-profiles")),
+                            ))
+                            .width(Length::Fill),
+                        )
+                        .push(widget::text::body(power_profile_name(profiles.active)))
+                        .push(
+                            widget::icon::from_name(if profiles.expanded {
+                                "go-down-symbolic"
+                            } else {
+                                "go-next-symbolic"
+                            })
+                            .size(16),
+                        ),
+                )
+                .on_press(messages.toggle_profiles.clone()),
+            );
+            if profiles.expanded {
+                for profile in profiles.available {
+                    let check: Element<'_, Message> = if profile == profiles.active {
+                        widget::icon::from_name("object-select-symbolic")
+                            .size(16)
+                            .into()
+                    } else {
+                        widget::Space::with_width(16).into()
+                    };
+                    content_list = content_list.push(
+                        menu_button(
+                            widget::row()
+                                .align_y(Alignment::Center)
+                                .spacing(space_xxs)
+                                .padding([0, 0, 0, space_s])
+                                .push(check)
+                                .push(widget::text::body(power_profile_name(profile))),
+                        )
+                        .on_press((messages.set_profile)(profile)),
+                    );
+                }
+            }
+        }
+        MenuItemType::Divider => content_list = content_list.push(divider()),
+    };
+
+    content_list
+}
+
+/// Localized names for the profiles power-profiles-daemon ships with.
+pub fn power_profile_name(profile: &str) -> String {
+    match profile {
+        "power-saver" => fl!("power-saver"),
+        "balanced" => fl!("balanced"),
+        "performance" => fl!("performance"),
+        other => other.to_owned(),
+    }
+}
+
+/// Item icons are icon theme names, or paths to image files.
+pub fn icon_handle(icon: &str) -> widget::icon::Handle {
+    if Path::new(icon).is_absolute() {
+        widget::icon::from_path(PathBuf::from(icon))
+    } else {
+        widget::icon::from_name(icon).handle()
+    }
+}
+
+// The item's label, after its icon if it has one
+fn label<'a, Message: 'static>(item: &MenuItem, text: String) -> Element<'a, Message> {
+    match item.icon() {
+        Some(icon) => widget::row()
+            .align_y(Alignment::Center)
+            .spacing(cosmic::theme::active().cosmic().spacing.space_xs)
+            .push(widget::icon(icon_handle(&icon)).size(16))
+            .push(widget::text::body(text))
+            .into(),
+        None => widget::text::body(text).into(),
+    }
+}
@@ -1,32 +1,109 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
-use crate::config::{load_config, update_config};
+use crate::apps::{self, DesktopApp};
+use crate::commands::{CommandCheck, CommandIndex};
+use crate::config::{load_config, load_state, update_config, update_state};
+use crate::history::History;
+use crate::icons;
+use crate::logos::{self, LogoGroup, PackLogo};
 use cosmic::app::context_drawer;
 use cosmic::app::context_drawer::ContextDrawer;
-use cosmic::cosmic_config::Config;
-use cosmic::iced::{Alignment, Length, Radius};
-use cosmic::iced_widget::{rule, scrollable};
+use cosmic::cosmic_config::{Config, ConfigSet};
+use cosmic::iced::keyboard::{Key, Modifiers, key::Named};
+use cosmic::iced::{
+    Alignment, Border, Event, Font, Length, Radius, Subscription, event, keyboard, mouse, time,
+    window,
+};
+use cosmic::iced_widget::text::Span;
+use cosmic::iced_widget::{rich_text, rule, scrollable, span};
 use cosmic::prelude::*;
 use cosmic::theme;
 use cosmic::widget::{
     self, Space, about, about::About, container, dropdown, menu, settings, toggler,
 };
 use liblog::fl;
-use liblog::{IMAGES, MenuItem, MenuItemType, MenuItems, PowerActionOption};
+use liblog::view::{self, icon_handle};
+use liblog::{
+    IMAGES, LAUNCH_LOG_KEY, LaunchRecord, LogoMenuConfig, MenuItem, MenuItemType, MenuItems,
+    PowerActionOption, ResourceLimits,
+};
 use rfd::FileDialog;
-use std::collections::{HashMap, VecDeque};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fmt;
 use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 const APP_ICON: &[u8] =
     include_bytes!("../../res/icons/hicolor/scalable/apps/dev.cappsy.CosmicExtAppletLogoMenu.svg");
 const CONFIG_VER: u64 = 1;
 const CONFIG_ID: &str = "dev.cappsy.CosmicExtAppletLogoMenu";
+const MIB: u64 = 1024 * 1024;
+// Icons drawn at once in the icon picker
+const ICON_PICKER_LIMIT: usize = 200;
+// Quiet time after the last change before it is written out
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+// Search boxes Ctrl+F can jump to
+const ITEM_SEARCH: &str = "item-search";
+const LOGO_SEARCH: &str = "logo-search";
+const APP_SEARCH: &str = "app-search";
+const ICON_SEARCH: &str = "icon-search";
+
+// Something a launch asks the window to do, handed to the running window if
+// there already is one
+#[derive(Clone, Debug, PartialEq)]
+pub enum WindowAction {
+    EditItem(usize),
+    Preview,
+}
+impl fmt::Display for WindowAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindowAction::EditItem(i) => write!(f, "edit-item:{}", i),
+            WindowAction::Preview => write!(f, "preview"),
+        }
+    }
+}
+impl FromStr for WindowAction {
+    type Err = String;
+
+    fn from_str(action: &str) -> Result<Self, Self::Err> {
+        match action.split_once(':') {
+            Some(("edit-item", i)) => i
+                .parse()
+                .map(WindowAction::EditItem)
+                .map_err(|_| format!("'{}' is not an item position", i)),
+            None if action == "preview" => Ok(WindowAction::Preview),
+            _ => Err(format!("unknown action '{}'", action)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Flags {
+    pub action: Option<WindowAction>,
+}
+impl cosmic::app::CosmicFlags for Flags {
+    type SubCommand = WindowAction;
+    type Args = Vec<String>;
+
+    fn action(&self) -> Option<&Self::SubCommand> {
+        self.action.as_ref()
+    }
+}
 
 #[derive(Clone, Debug)]
 pub enum DialogPage {
     EditItem(usize, MenuItem),
     RemoveItem(usize),
     ResetMenu,
+    Conflict,
+    // search text
+    PickApp(String),
+    // search text, symbolic icons only
+    PickIcon(String, bool),
 }
 
 pub struct AppModel {
@@ -38,27 +115,99 @@ pub struct AppModel {
     about_page: About,
 
     logo_options: Vec<String>,
+    logo_groups: Vec<LogoGroup>,
+    logo_packs: Vec<PackLogo>,
+    logo_search: String,
     selected_logo_idx: Option<usize>,
     selected_logo_name: String,
     custom_logo_active: bool,
     custom_logo_path: String,
+    notify_on_failure: bool,
     menu_items: Vec<MenuItem>,
     menu_types: Vec<MenuItemType>,
     menu_type_labels: Vec<String>,
     power_actions: Vec<PowerActionOption>,
     power_action_labels: Vec<String>,
+    launch_log: Vec<LaunchRecord>,
+    // (dragged row, row it would be dropped on)
+    drag: Option<(usize, usize)>,
+    // row the keyboard shortcuts act on
+    focused: Option<usize>,
+    // only rows matching this are listed
+    item_filter: String,
+    // a search box has focus, so the arrow keys move its cursor instead of the row focus
+    typing: bool,
+    // rows ticked while in selection mode
+    selecting: bool,
+    selected: BTreeSet<usize>,
+    history: History<Snapshot>,
+    toasts: widget::Toasts<Message>,
+    // What the config held when last read or written
+    saved: Snapshot,
+    // Bumped on every change so the save timer restarts
+    pending_save: Option<u64>,
+    save_counter: u64,
+    // filled in once PATH has been scanned
+    commands: Option<Arc<CommandIndex>>,
+    // each command is checked once against the index, not on every redraw
+    command_checks: RefCell<HashMap<String, CommandCheck>>,
+    // read the first time the app picker opens
+    apps: Option<Vec<DesktopApp>>,
+    // names from the icon theme, read the first time the icon picker opens
+    icons: Option<Vec<String>>,
+    // the preview has no power-profiles-daemon to ask, so keeps its own
+    preview_profile: String,
+    preview_profiles_expanded: bool,
+    preview_submenus: Vec<Vec<usize>>,
+}
+
+// Everything undo and redo can restore
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Snapshot {
+    menu_items: Vec<MenuItem>,
+    selected_logo_idx: Option<usize>,
+    selected_logo_name: String,
+    custom_logo_active: bool,
+    custom_logo_path: String,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     ToggleContextPage(ContextPage),
     UpdateLogo(usize),
+    LogoSearch(String),
+    // pack logos are used as the custom logo
+    SelectLogoFile(String),
     ToggleCustomLogo(bool),
     UpdateCustomLogo,
+    ToggleNotifyOnFailure(bool),
     AddItem(MenuItemType),
     SaveItem(usize, MenuItem),
     RemoveItem(usize),
+    ToggleItem(usize, bool),
+    DuplicateItem(usize),
+    Ungroup(usize),
+    ToggleSelecting,
+    SelectItem(usize, bool),
+    SelectAll,
+    BulkRemove,
+    BulkEnable(bool),
+    BulkMove(OrderDirection),
+    GroupSubmenu,
+    GroupHeader,
     MoveItem(OrderDirection, usize),
+    FocusItem(usize),
+    FocusStep(OrderDirection),
+    EditFocused,
+    RemoveFocused,
+    MoveFocused(OrderDirection),
+    Confirm,
+    Cancel,
+    Search,
+    DragStart(usize),
+    DragOver(usize),
+    DragDrop,
+    DragCancel,
     ResetMenu,
     DialogUpdate(DialogPage),
     DialogCancel,
@@ -66,17 +215,41 @@ pub enum Message {
     DialogRemoveItem(usize),
     DialogResetMenu,
     OpenUrl(String),
+    ClearLaunchLog,
+    Undo,
+    Redo,
+    Key(Modifiers, Key),
+    CloseToast(widget::ToastId),
+    Save,
+    KeepChanges,
+    LoadSaved,
+    ConfigChanged(LogoMenuConfig),
+    CommandIndex(Arc<CommandIndex>),
+    DialogPickApp,
+    Apps(Vec<DesktopApp>),
+    PickApp(DesktopApp),
+    DialogPickIcon,
+    Icons(Vec<String>),
+    PickIcon(String),
+    PickIconFile,
+    TogglePreviewProfiles,
+    PreviewProfile(String),
+    PreviewSubmenu(Vec<usize>),
+    ItemFilter(String),
+    Typing(bool),
 }
 
 #[derive(Debug, Clone)]
 pub enum OrderDirection {
     Up,
     Down,
+    Top,
+    Bottom,
 }
 
 impl cosmic::Application for AppModel {
     type Executor = cosmic::executor::Default;
-    type Flags = ();
+    type Flags = Flags;
     type Message = Message;
 
     const APP_ID: &'static str = "dev.cappsy.CosmicExtAppletLogoMenu.Settings";
@@ -89,10 +262,7 @@ impl cosmic::Application for AppModel {
         &mut self.core
     }
 
-    fn init(
-        core: cosmic::Core,
-        _flags: Self::Flags,
-    ) -> (Self, Task<cosmic::Action<Self::Message>>) {
+    fn init(core: cosmic::Core, flags: Self::Flags) -> (Self, Task<cosmic::Action<Self::Message>>) {
         // Get the current logo, with fallbacks to the default
         let default_logo = String::from("Cosmic (Symbolic)");
         let config_logo = match load_config("logo", CONFIG_VER) {
@@ -127,7 +297,15 @@ impl cosmic::Application for AppModel {
             None => "".to_owned(),
         };
 
-        let menu_types = vec![MenuItemType::LaunchAction, MenuItemType::PowerAction];
+        // failure notifications are on unless turned off
+        let notify_on_failure = load_config("notify_on_failure", CONFIG_VER).unwrap_or(true);
+
+        let menu_types = vec![
+            MenuItemType::LaunchAction,
+            MenuItemType::PowerAction,
+            MenuItemType::PowerProfiles,
+            MenuItemType::Header,
+        ];
         let menu_type_labels: Vec<String> =
             menu_types.iter().map(|t| t.as_localized_string()).collect();
 
@@ -137,6 +315,8 @@ impl cosmic::Application for AppModel {
             PowerActionOption::Suspend,
             PowerActionOption::Restart,
             PowerActionOption::Shutdown,
+            PowerActionOption::ScheduleRestart,
+            PowerActionOption::ScheduleShutdown,
         ];
         let power_action_labels: Vec<String> = power_actions
             .iter()
@@ -146,34 +326,136 @@ impl cosmic::Application for AppModel {
         let mut app = AppModel {
             core,
             context_page: ContextPage::default(),
-            key_binds: HashMap::new(),
+            key_binds: key_binds(),
             config: Config::new(CONFIG_ID, CONFIG_VER).unwrap(),
             dialog_pages: VecDeque::new(),
             about_page: build_about(),
             logo_options,
+            logo_groups: logos::groups(),
+            logo_packs: logos::installed_packs(),
+            logo_search: String::new(),
             selected_logo_idx,
             selected_logo_name,
             menu_items,
             custom_logo_active,
             custom_logo_path,
+            notify_on_failure,
             menu_types,
             menu_type_labels,
             power_actions,
             power_action_labels,
+            launch_log: Vec::new(),
+            drag: None,
+            focused: None,
+            item_filter: String::new(),
+            typing: false,
+            selecting: false,
+            selected: BTreeSet::new(),
+            history: History::default(),
+            toasts: widget::Toasts::new(Message::CloseToast),
+            saved: Snapshot::default(),
+            pending_save: None,
+            save_counter: 0,
+            commands: None,
+            command_checks: RefCell::default(),
+            apps: None,
+            icons: None,
+            preview_profile: String::from("balanced"),
+            preview_profiles_expanded: false,
+            preview_submenus: Vec::new(),
         };
+        app.saved = app.snapshot();
 
         let command = app.update_title();
-        (app, command)
+        let scan = Task::perform(CommandIndex::scan(), |index| {
+            cosmic::action::app(Message::CommandIndex(Arc::new(index)))
+        });
+        let action = match flags.action {
+            Some(action) => app.run_action(action),
+            None => Task::none(),
+        };
+        (app, Task::batch([command, scan, action]))
+    }
+
+    // Launching again while open lands here instead of starting a second window
+    fn dbus_activation(
+        &mut self,
+        msg: cosmic::dbus_activation::Message,
+    ) -> Task<cosmic::Action<Self::Message>> {
+        let mut tasks = Vec::new();
+        if let Some(id) = self.core.main_window_id() {
+            tasks.push(window::gain_focus(id));
+        }
+        if let cosmic::dbus_activation::Details::ActivateAction { action, .. } = msg.msg {
+            match action.parse::<WindowAction>() {
+                Ok(action) => tasks.push(self.run_action(action)),
+                Err(e) => eprintln!("cosmic-ext-applet-logomenu ERROR: '{}'", e),
+            }
+        }
+        Task::batch(tasks)
     }
 
     fn header_start(&self) -> Vec<Element<'_, Self::Message>> {
-        let menu_bar = menu::bar(vec![menu::Tree::with_children(
-            menu::root(fl!("view")).apply(Element::from),
-            menu::items(
-                &self.key_binds,
-                vec![menu::Item::Button(fl!("about"), None, MenuAction::About)],
+        let undo = if self.history.can_undo() {
+            menu::Item::Button(fl!("undo"), None, MenuAction::Undo)
+        } else {
+            menu::Item::ButtonDisabled(fl!("undo"), None, MenuAction::Undo)
+        };
+        let redo = if self.history.can_redo() {
+            menu::Item::Button(fl!("redo"), None, MenuAction::Redo)
+        } else {
+            menu::Item::ButtonDisabled(fl!("redo"), None, MenuAction::Redo)
+        };
+
+        // item shortcuts act on the focused row
+        let on_focused = |label: String, action: MenuAction| {
+            if self.focused.is_some() {
+                menu::Item::Button(label, None, action)
+            } else {
+                menu::Item::ButtonDisabled(label, None, action)
+            }
+        };
+        // adding goes through the add buttons, which selection mode hides
+        let unless_selecting = |label: String, action: MenuAction| {
+            if self.selecting {
+                menu::Item::ButtonDisabled(label, None, action)
+            } else {
+                menu::Item::Button(label, None, action)
+            }
+        };
+
+        let menu_bar = menu::bar(vec![
+            menu::Tree::with_children(
+                menu::root(fl!("edit")).apply(Element::from),
+                menu::items(
+                    &self.key_binds,
+                    vec![
+                        undo,
+                        redo,
+                        menu::Item::Divider,
+                        unless_selecting(fl!("add-menu-item"), MenuAction::AddItem),
+                        unless_selecting(fl!("add-divider"), MenuAction::AddDivider),
+                        on_focused(fl!("edit-menu-item"), MenuAction::EditItem),
+                        on_focused(fl!("remove"), MenuAction::RemoveItem),
+                        on_focused(fl!("move-up"), MenuAction::MoveUp),
+                        on_focused(fl!("move-down"), MenuAction::MoveDown),
+                        menu::Item::Divider,
+                        menu::Item::Button(fl!("search"), None, MenuAction::Search),
+                    ],
+                ),
+            ),
+            menu::Tree::with_children(
+                menu::root(fl!("view")).apply(Element::from),
+                menu::items(
+                    &self.key_binds,
+                    vec![
+                        menu::Item::Button(fl!("preview"), None, MenuAction::Preview),
+                        menu::Item::Button(fl!("launch-log"), None, MenuAction::LaunchLog),
+                        menu::Item::Button(fl!("about"), None, MenuAction::About),
+                    ],
+                ),
             ),
-        )]);
+        ]);
 
         vec![menu_bar.into()]
     }
@@ -192,6 +474,28 @@ impl cosmic::Application for AppModel {
                 header_actions: Vec::new(),
                 footer: None,
             }),
+            ContextPage::LaunchLog => Some(ContextDrawer {
+                title: Some(fl!("launch-log").into()),
+                content: self.launch_log_view(),
+                on_close: Message::ToggleContextPage(ContextPage::LaunchLog),
+                header: None,
+                header_actions: Vec::new(),
+                footer: Some(
+                    widget::button::standard(fl!("clear"))
+                        .on_press_maybe(
+                            (!self.launch_log.is_empty()).then_some(Message::ClearLaunchLog),
+                        )
+                        .into(),
+                ),
+            }),
+            ContextPage::Preview => Some(ContextDrawer {
+                title: Some(fl!("preview").into()),
+                content: self.preview(),
+                on_close: Message::ToggleContextPage(ContextPage::Preview),
+                header: None,
+                header_actions: Vec::new(),
+                footer: None,
+            }),
         }
     }
 
@@ -272,23 +576,30 @@ impl cosmic::Application for AppModel {
                     ),
                 )
             });
-        } else {
-            menu_settings = menu_settings.add({
-                Element::from(settings::item::builder(fl!("logo")).control(dropdown(
-                    &self.logo_options,
-                    self.selected_logo_idx,
-                    Message::UpdateLogo,
-                )))
-            })
         }
 
+        menu_settings = menu_settings.add({
+            Element::from(
+                settings::item::builder(fl!("notify-on-failure")).control(
+                    toggler(self.notify_on_failure)
+                        .on_toggle(|value| Message::ToggleNotifyOnFailure(value)),
+                ),
+            )
+        });
+
         page_content = page_content.push(menu_settings);
         page_content = page_content.push(Space::with_height(padding));
 
-        // Add buttons
-        page_content = page_content.push(
+        // Logo gallery
+        page_content = page_content.push(self.logo_gallery());
+        page_content = page_content.push(Space::with_height(padding));
+
+        // Add buttons, or what can be done with the selection
+        page_content = page_content.push(if self.selecting {
+            self.selection_bar()
+        } else {
             container(
-                widget::row::with_capacity(3)
+                widget::row::with_capacity(4)
                     .push(
                         widget::button::suggested(fl!("add-menu-item"))
                             .on_press(Message::AddItem(MenuItemType::LaunchAction))
@@ -299,6 +610,11 @@ impl cosmic::Application for AppModel {
                             .on_press(Message::AddItem(MenuItemType::Divider))
                             .apply(Element::from),
                     )
+                    .push(
+                        widget::button::standard(fl!("select"))
+                            .on_press(Message::ToggleSelecting)
+                            .apply(Element::from),
+                    )
                     .push(
                         widget::button::destructive(fl!("reset-to-default"))
                             .on_press(Message::DialogResetMenu)
@@ -308,52 +624,121 @@ impl cosmic::Application for AppModel {
                     .apply(Element::from),
             )
             .width(Length::Fill)
-            .align_x(Alignment::Center),
-        );
+            .align_x(Alignment::Center)
+            .into()
+        });
         page_content = page_content.push(Space::with_height(15));
 
+        // Filter
+        page_content = page_content.push(
+            widget::search_input(fl!("filter-items"), self.item_filter.clone())
+                .id(widget::Id::new(ITEM_SEARCH))
+                .on_input(Message::ItemFilter)
+                .on_focus(Message::Typing(true))
+                .on_unfocus(Message::Typing(false)),
+        );
+        page_content = page_content.push(Space::with_height(10));
+
         // Menu builder
         let mut menu_item_controls = settings::section();
         let menu_items = &self.menu_items;
 
-        for (i, menu_item) in menu_items.iter().enumerate() {
-            let mut menu_item_row = widget::row().push(
-                widget::row::with_capacity(2)
+        // rows the filter hides are skipped, so i is always the real position
+        let visible = self.visible_items();
+        if visible.is_empty() && !menu_items.is_empty() {
+            menu_item_controls = menu_item_controls.add(
+                container(widget::text::body(fl!("no-matching-items")))
+                    .width(Length::Fill)
+                    .align_x(Alignment::Center)
+                    .padding(10),
+            );
+        }
+        for &i in &visible {
+            let menu_item = &menu_items[i];
+            let mut menu_item_row = widget::row();
+            if self.selecting {
+                menu_item_row = menu_item_row.push(
+                    container(
+                        widget::checkbox("", self.selected.contains(&i))
+                            .on_toggle(move |value| Message::SelectItem(i, value)),
+                    )
+                    .padding([8, 10, 0, 0]),
+                );
+            } else {
+                menu_item_row = menu_item_row
                     .push(
-                        widget::button::icon(widget::icon::from_name("pan-up-symbolic"))
-                            .on_press(Message::MoveItem(OrderDirection::Up, i)),
+                        widget::mouse_area(
+                            container(widget::icon::from_name("list-drag-handle-symbolic"))
+                                .padding([8, 5, 0, 0]),
+                        )
+                        .interaction(mouse::Interaction::Grab)
+                        .on_press(Message::DragStart(i)),
                     )
                     .push(
-                        widget::button::icon(widget::icon::from_name("pan-down-symbolic"))
-                            .on_press(Message::MoveItem(OrderDirection::Down, i)),
-                    ),
-            );
+                        widget::row::with_capacity(4)
+                            .push(
+                                widget::button::icon(widget::icon::from_name("go-top-symbolic"))
+                                    .on_press(Message::MoveItem(OrderDirection::Top, i)),
+                            )
+                            .push(
+                                widget::button::icon(widget::icon::from_name("pan-up-symbolic"))
+                                    .on_press(Message::MoveItem(OrderDirection::Up, i)),
+                            )
+                            .push(
+                                widget::button::icon(widget::icon::from_name("pan-down-symbolic"))
+                                    .on_press(Message::MoveItem(OrderDirection::Down, i)),
+                            )
+                            .push(
+                                widget::button::icon(widget::icon::from_name("go-bottom-symbolic"))
+                                    .on_press(Message::MoveItem(OrderDirection::Bottom, i)),
+                            ),
+                    );
+            }
 
-            // item icon if not Divider
-            if menu_item.item_type() != MenuItemType::Divider {
+            // item icon if not Divider, or a warning if its command won't run
+            if let Some(check) = self.command_check(menu_item) {
+                menu_item_row = menu_item_row.push(widget::tooltip(
+                    container(widget::icon::from_name("dialog-warning-symbolic"))
+                        .padding([8, 15, 0, 10]),
+                    widget::text(check_message(&check)),
+                    widget::tooltip::Position::Bottom,
+                ))
+            } else if let Some(icon) = menu_item.icon() {
+                menu_item_row = menu_item_row.push(
+                    container(widget::icon(icon_handle(&icon)).size(16)).padding([8, 15, 0, 10]),
+                )
+            } else if menu_item.item_type() != MenuItemType::Divider {
                 menu_item_row = menu_item_row.push(
                     container(widget::icon::from_name(match menu_item.item_type() {
                         MenuItemType::LaunchAction => "utilities-terminal-symbolic",
                         MenuItemType::PowerAction => "system-shutdown-symbolic",
+                        MenuItemType::PowerProfiles => "power-profile-balanced-symbolic",
+                        MenuItemType::Header => "format-justify-left-symbolic",
+                        MenuItemType::Submenu => "folder-symbolic",
                         _ => "",
                     }))
                     .padding([8, 15, 0, 10]),
                 )
             }
 
-            // item label and controls
-            menu_item_row = menu_item_row
-                .push(match menu_item.label() {
+            // item label
+            menu_item_row =
+                menu_item_row.push(match menu_item.label() {
                     Some(label) => {
                         let mut label_string = label;
-                        let command_string = menu_item.command().unwrap_or_default();
+                        let command_string = match menu_item.item_type() {
+                            MenuItemType::Submenu => {
+                                fl!("submenu-items", count = menu_item.children.len())
+                            }
+                            _ => menu_item.command().unwrap_or_default(),
+                        };
 
                         if command_string != "" {
                             label_string.push_str("   ::   ");
                             label_string.push_str(&command_string);
                         }
 
-                        container(cosmic::widget::text(label_string))
+                        container(highlighted(label_string, &self.item_filter))
                             .width(Length::Fill)
                             .padding([5, 10, 0, 0])
                     }
@@ -375,38 +760,122 @@ impl cosmic::Application for AppModel {
                         }),
                     ))
                     .padding([15, 10]),
-                })
-                .push(
-                    widget::row::with_capacity(2)
-                        .push(
-                            widget::button::icon(widget::icon::from_name("edit-symbolic"))
-                                .on_press_maybe(match menu_item.item_type() {
-                                    MenuItemType::Divider => None,
-                                    _ => Some(Message::DialogEditItem(i, menu_item.clone())),
-                                }),
-                        )
-                        .push(
-                            widget::button::icon(widget::icon::from_name("edit-delete-symbolic"))
-                                .on_press(Message::DialogRemoveItem(i)),
-                        ),
+                });
+
+            if !self.selecting {
+                let mut item_controls = widget::row::with_capacity(5).push(
+                    container(
+                        toggler(menu_item.enabled)
+                            .on_toggle(move |value| Message::ToggleItem(i, value)),
+                    )
+                    .padding([8, 5, 0, 0]),
                 );
+                if menu_item.item_type() == MenuItemType::Submenu {
+                    item_controls = item_controls.push(widget::tooltip(
+                        widget::button::icon(widget::icon::from_name("view-restore-symbolic"))
+                            .on_press(Message::Ungroup(i)),
+                        widget::text(fl!("ungroup")),
+                        widget::tooltip::Position::Bottom,
+                    ));
+                }
+                item_controls = item_controls
+                    .push(widget::tooltip(
+                        widget::button::icon(widget::icon::from_name("edit-copy-symbolic"))
+                            .on_press(Message::DuplicateItem(i)),
+                        widget::text(fl!("duplicate")),
+                        widget::tooltip::Position::Bottom,
+                    ))
+                    .push(
+                        widget::button::icon(widget::icon::from_name("edit-symbolic"))
+                            .on_press_maybe(match menu_item.item_type() {
+                                MenuItemType::Divider => None,
+                                _ => Some(Message::DialogEditItem(i, menu_item.clone())),
+                            }),
+                    )
+                    .push(
+                        widget::button::icon(widget::icon::from_name("edit-delete-symbolic"))
+                            .on_press(Message::DialogRemoveItem(i)),
+                    );
+                menu_item_row = menu_item_row.push(item_controls);
+            }
+
+            // disabled rows are dimmed but stay editable
+            let menu_item_row: Element<'_, Message> = if menu_item.enabled {
+                menu_item_row.into()
+            } else {
+                container(menu_item_row)
+                    .class(theme::Container::custom(|theme| {
+                        let text = theme.cosmic().on_bg_color();
+                        let dimmed =
+                            cosmic::iced::Color::from_rgba(text.red, text.green, text.blue, 0.5);
+
+                        cosmic::iced::widget::container::Style {
+                            text_color: Some(dimmed),
+                            icon_color: Some(dimmed),
+                            ..Default::default()
+                        }
+                    }))
+                    .into()
+            };
+
+            // while dragging, rows track the pointer and the drop target is outlined
+            let menu_item_row: Element<'_, Message> = match self.drag {
+                Some((from, over)) => {
+                    let is_target = over == i && from != i;
+                    widget::mouse_area(container(menu_item_row).class(theme::Container::custom(
+                        move |theme| {
+                            let cosmic = theme.cosmic();
+                            let accent = cosmic.accent_color();
+
+                            cosmic::iced::widget::container::Style {
+                                border: Border {
+                                    color: cosmic::iced::Color::from_rgb(
+                                        accent.red,
+                                        accent.green,
+                                        accent.blue,
+                                    ),
+                                    width: if is_target { 2.0 } else { 0.0 },
+                                    radius: Radius::new(4),
+                                },
+                                ..Default::default()
+                            }
+                        },
+                    )))
+                    .on_enter(Message::DragOver(i))
+                    .into()
+                }
+                // clicking a row gives it keyboard focus, shown by a thin outline
+                None => {
+                    let is_focused = self.focused == Some(i);
+                    widget::mouse_area(container(menu_item_row).class(theme::Container::custom(
+                        move |theme| {
+                            let accent = theme.cosmic().accent_color();
+
+                            cosmic::iced::widget::container::Style {
+                                border: Border {
+                                    color: cosmic::iced::Color::from_rgb(
+                                        accent.red,
+                                        accent.green,
+                                        accent.blue,
+                                    ),
+                                    width: if is_focused { 1.0 } else { 0.0 },
+                                    radius: Radius::new(4),
+                                },
+                                ..Default::default()
+                            }
+                        },
+                    )))
+                    .on_press(Message::FocusItem(i))
+                    .into()
+                }
+            };
 
             // apply row to list
-            menu_item_controls = menu_item_controls.add(cosmic::Element::from(menu_item_row));
+            menu_item_controls = menu_item_controls.add(menu_item_row);
         }
         page_content = page_content.push(menu_item_controls);
         page_content = page_content.push(Space::with_height(15));
 
-        // TODO: This works for now but it needs to be moved away
-        // from the view function so it only triggers when needed.
-        update_config(
-            self.config.clone(),
-            "menu_items",
-            MenuItems {
-                items: self.menu_items.clone(),
-            },
-        );
-
         // Combine all elements to finished page
         let page_container = container(page_content)
             .max_width(600)
@@ -415,8 +884,17 @@ impl cosmic::Application for AppModel {
             .center_x(Length::Fill)
             .padding([0, padding]);
 
+        // Dropping anywhere lands the row on the outlined target, see subscription
+        let page_container: Element<_> = if self.drag.is_some() {
+            widget::mouse_area(page_container)
+                .interaction(mouse::Interaction::Grabbing)
+                .into()
+        } else {
+            page_container.into()
+        };
+
         // Display
-        let content: Element<_> = scrollable(page_container).into();
+        let content: Element<_> = widget::toaster(&self.toasts, scrollable(page_container));
 
         content
     }
@@ -483,13 +961,10 @@ impl cosmic::Application for AppModel {
                         .push(
                             widget::text_input("", label.clone())
                                 .on_input(move |value| {
-                                    Message::DialogUpdate(DialogPage::EditItem(
-                                        *i,
-                                        MenuItem {
-                                            label: Some(value),
-                                            ..menu_item.clone()
-                                        },
-                                    ))
+                                    // typed text replaces a translated label
+                                    let mut menu_item = menu_item.clone();
+                                    menu_item.set_label(value);
+                                    Message::DialogUpdate(DialogPage::EditItem(*i, menu_item))
                                 })
                                 .width(Length::Fill),
                         ),
@@ -555,30 +1030,286 @@ impl cosmic::Application for AppModel {
                     )
                 };
 
-                // validation
-                let complete_maybe = if label.is_empty() {
-                    None
-                } else {
-                    Some(Message::SaveItem(*i, menu_item.clone()))
-                };
-
                 let save_button = widget::button::suggested(fl!("save"))
-                    .on_press_maybe(complete_maybe)
+                    .on_press_maybe(self.dialog_confirm())
                     .apply(Element::from);
 
                 let cancel_button =
                     widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel);
 
-                widget::dialog()
+                // submenus keep their type, their items are edited by ungrouping
+                let mut controls = widget::ListColumn::default();
+                if item_type != MenuItemType::Submenu {
+                    controls = controls.add(type_input);
+                }
+                controls = controls.add(label_input);
+
+                // only launchers and power actions have something to run
+                let runs_command = matches!(
+                    item_type,
+                    MenuItemType::LaunchAction | MenuItemType::PowerAction
+                );
+                if runs_command {
+                    controls = controls.add(command_input);
+                }
+                if runs_command || item_type == MenuItemType::Submenu {
+                    let icon = menu_item.icon();
+                    let mut icon_row = widget::row::with_capacity(4).push(
+                        widget::text(fl!("icon"))
+                            .align_y(Alignment::Center)
+                            .height(30)
+                            .width(120),
+                    );
+                    if let Some(icon) = &icon {
+                        icon_row = icon_row.push(
+                            container(widget::icon(icon_handle(icon)).size(24))
+                                .padding([3, 10, 0, 0]),
+                        );
+                    }
+                    let icon_row = icon_row.push(
+                        widget::text_input("", icon.unwrap_or_default())
+                            .on_input(|value| {
+                                Message::DialogUpdate(DialogPage::EditItem(
+                                    *i,
+                                    MenuItem {
+                                        icon: Some(value).filter(|v| !v.is_empty()),
+                                        ..menu_item.clone()
+                                    },
+                                ))
+                            })
+                            .width(Length::Fill),
+                    );
+                    let icon_input = icon_row
+                        .push(
+                            widget::button::standard(fl!("choose-icon"))
+                                .on_press(Message::DialogPickIcon),
+                        )
+                        .spacing(5);
+
+                    controls = controls.add(container(icon_input));
+                }
+
+                // saving is still allowed, the program may be installed later
+                if let Some(check) = self.command_check(menu_item) {
+                    let mut warning = widget::column()
+                        .push(
+                            widget::row::with_capacity(2)
+                                .push(widget::icon::from_name("dialog-warning-symbolic"))
+                                .push(widget::text::body(check_message(&check)))
+                                .spacing(10)
+                                .align_y(Alignment::Center),
+                        )
+                        .spacing(5);
+
+                    if let CommandCheck::NotFound(program, suggestions) = &check {
+                        if !suggestions.is_empty() {
+                            let mut row = widget::row()
+                                .push(widget::text::caption(fl!("did-you-mean")))
+                                .spacing(5)
+                                .align_y(Alignment::Center);
+                            for suggestion in suggestions {
+                                row = row.push(widget::button::text(suggestion.clone()).on_press(
+                                    Message::DialogUpdate(DialogPage::EditItem(
+                                        *i,
+                                        MenuItem {
+                                            command: Some(command.replacen(
+                                                program.as_str(),
+                                                suggestion,
+                                                1,
+                                            )),
+                                            ..menu_item.clone()
+                                        },
+                                    )),
+                                ));
+                            }
+                            warning = warning.push(row);
+                        }
+                    }
+
+                    controls = controls.add(warning);
+                }
+
+                // optional systemd resource controls for launched items
+                if item_type == MenuItemType::LaunchAction {
+                    let resources = menu_item.resources();
+                    let with_resources = {
+                        let menu_item = menu_item.clone();
+                        let i = *i;
+                        move |resources| {
+                            Message::DialogUpdate(DialogPage::EditItem(
+                                i,
+                                MenuItem {
+                                    resources,
+                                    ..menu_item.clone()
+                                },
+                            ))
+                        }
+                    };
+
+                    controls = controls
+                        .add(resource_input(
+                            fl!("memory-limit"),
+                            resources.memory_max.map(|bytes| bytes / MIB),
+                            |mib: u64| mib > 0 && mib.checked_mul(MIB).is_some(),
+                            {
+                                let resources = resources.clone();
+                                let with_resources = with_resources.clone();
+                                move |value| {
+                                    with_resources(ResourceLimits {
+                                        memory_max: value.and_then(|mib| mib.checked_mul(MIB)),
+                                        ..resources.clone()
+                                    })
+                                }
+                            },
+                        ))
+                        .add(resource_input(
+                            fl!("cpu-weight"),
+                            resources.cpu_weight,
+                            |weight| ResourceLimits::CPU_WEIGHT.contains(&weight),
+                            {
+                                let resources = resources.clone();
+                                let with_resources = with_resources.clone();
+                                move |value| {
+                                    with_resources(ResourceLimits {
+                                        cpu_weight: value,
+                                        ..resources.clone()
+                                    })
+                                }
+                            },
+                        ))
+                        .add(resource_input(
+                            fl!("nice"),
+                            resources.nice,
+                            |nice| ResourceLimits::NICE.contains(&nice),
+                            {
+                                let resources = resources.clone();
+                                move |value| {
+                                    with_resources(ResourceLimits {
+                                        nice: value,
+                                        ..resources.clone()
+                                    })
+                                }
+                            },
+                        ));
+                }
+
+                let mut dialog = widget::dialog()
                     .title(fl!("edit-menu-item"))
+                    .control(controls)
+                    .primary_action(save_button)
+                    .secondary_action(cancel_button);
+                if item_type == MenuItemType::LaunchAction {
+                    dialog = dialog.tertiary_action(
+                        widget::button::text(fl!("choose-application"))
+                            .on_press(Message::DialogPickApp),
+                    );
+                }
+                dialog.apply(Element::from)
+            }
+
+            DialogPage::PickIcon(query, symbolic_only) => {
+                let symbolic_only = *symbolic_only;
+                let search = widget::search_input(fl!("search-icons"), query.clone())
+                    .id(widget::Id::new(ICON_SEARCH))
+                    .on_input(move |value| {
+                        Message::DialogUpdate(DialogPage::PickIcon(value, symbolic_only))
+                    });
+                let filter = {
+                    let query = query.clone();
+                    toggler(symbolic_only)
+                        .label(fl!("symbolic-only"))
+                        .on_toggle(move |value| {
+                            Message::DialogUpdate(DialogPage::PickIcon(query.clone(), value))
+                        })
+                };
+
+                let icons: Element<'_, Message> = match &self.icons {
+                    None => widget::text::body(fl!("loading")).into(),
+                    Some(names) => {
+                        let query = query.to_lowercase();
+                        let mut matches = names.iter().filter(|name| {
+                            (!symbolic_only || icons::is_symbolic(name))
+                                && name.to_lowercase().contains(&query)
+                        });
+
+                        // themes ship thousands of icons, so only draw the first few
+                        let buttons: Vec<Element<'_, Message>> = matches
+                            .by_ref()
+                            .take(ICON_PICKER_LIMIT)
+                            .map(|name| {
+                                widget::tooltip(
+                                    widget::button::icon(widget::icon::from_name(name.as_str()))
+                                        .icon_size(32)
+                                        .on_press(Message::PickIcon(name.clone())),
+                                    widget::text(name.clone()),
+                                    widget::tooltip::Position::Bottom,
+                                )
+                                .into()
+                            })
+                            .collect();
+
+                        let mut grid = widget::column()
+                            .push(widget::flex_row(buttons).row_spacing(5).column_spacing(5))
+                            .spacing(10);
+                        if matches.next().is_some() {
+                            grid = grid.push(widget::text::caption(fl!("refine-search")));
+                        }
+                        scrollable(grid).height(300).into()
+                    }
+                };
+
+                widget::dialog()
+                    .title(fl!("choose-icon"))
                     .control(
-                        widget::ListColumn::default()
-                            .add(type_input)
-                            .add(label_input)
-                            .add(command_input),
+                        widget::column()
+                            .push(search)
+                            .push(filter)
+                            .push(icons)
+                            .spacing(10),
+                    )
+                    .secondary_action(
+                        widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                    )
+                    .tertiary_action(
+                        widget::button::text(fl!("choose-file")).on_press(Message::PickIconFile),
+                    )
+                    .apply(Element::from)
+            }
+
+            DialogPage::PickApp(query) => {
+                let search = widget::search_input(fl!("search-applications"), query.clone())
+                    .id(widget::Id::new(APP_SEARCH))
+                    .on_input(|value| Message::DialogUpdate(DialogPage::PickApp(value)));
+
+                let apps: Element<'_, Message> = match &self.apps {
+                    None => widget::text::body(fl!("loading")).into(),
+                    Some(apps) => {
+                        let mut list = widget::column().spacing(2);
+                        for app in apps.iter().filter(|app| app.matches(query)) {
+                            let icon = app.icon.as_deref().unwrap_or("application-x-executable");
+                            list = list.push(
+                                widget::button::custom(
+                                    widget::row::with_capacity(2)
+                                        .push(widget::icon(icon_handle(icon)).size(24))
+                                        .push(widget::text::body(app.name.clone()))
+                                        .spacing(10)
+                                        .align_y(Alignment::Center),
+                                )
+                                .class(theme::Button::Text)
+                                .width(Length::Fill)
+                                .on_press(Message::PickApp(app.clone())),
+                            );
+                        }
+                        scrollable(list).height(300).into()
+                    }
+                };
+
+                widget::dialog()
+                    .title(fl!("choose-application"))
+                    .control(widget::column().push(search).push(apps).spacing(10))
+                    .secondary_action(
+                        widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
                     )
-                    .primary_action(save_button)
-                    .secondary_action(cancel_button)
                     .apply(Element::from)
             }
 
@@ -601,11 +1332,65 @@ impl cosmic::Application for AppModel {
                     widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
                 )
                 .apply(Element::from),
+
+            DialogPage::Conflict => widget::dialog()
+                .title(fl!("config-conflict"))
+                .body(fl!("config-conflict-body"))
+                .primary_action(
+                    widget::button::suggested(fl!("keep-changes")).on_press(Message::KeepChanges),
+                )
+                .secondary_action(
+                    widget::button::standard(fl!("load-saved")).on_press(Message::LoadSaved),
+                )
+                .apply(Element::from),
         };
 
         Some(dialog)
     }
 
+    fn subscription(&self) -> Subscription<Self::Message> {
+        let mut subscriptions = vec![event::listen_with(|event, status, _| match event {
+            Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. })
+                if status == event::Status::Ignored =>
+            {
+                Some(Message::Key(modifiers, key))
+            }
+            _ => None,
+        })];
+
+        // the release can land outside the page or the window, so listen for it here
+        if self.drag.is_some() {
+            subscriptions.push(event::listen_with(|event, _, _| match event {
+                Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                    Some(Message::DragDrop)
+                }
+                Event::Window(window::Event::Unfocused) => Some(Message::DragCancel),
+                _ => None,
+            }));
+        }
+
+        subscriptions.push(
+            self.core()
+                .watch_config::<LogoMenuConfig>(CONFIG_ID)
+                .map(|update| Message::ConfigChanged(update.config)),
+        );
+
+        // a new id drops the old timer, so only the last change in a burst saves
+        if let Some(id) = self.pending_save {
+            subscriptions.push(time::every(SAVE_DELAY).with(id).map(|_| Message::Save));
+        }
+
+        Subscription::batch(subscriptions)
+    }
+
+    fn on_app_exit(&mut self) -> Option<Self::Message> {
+        // flush a pending save, unless it would clobber an outside edit
+        if self.pending_save.take().is_some() && self.stored_snapshot() == self.saved {
+            self.persist();
+        }
+        None
+    }
+
     fn update(&mut self, message: Self::Message) -> Task<cosmic::Action<Self::Message>> {
         match message {
             Message::OpenUrl(url) => match open::that_detached(&url) {
@@ -614,6 +1399,10 @@ impl cosmic::Application for AppModel {
             },
 
             Message::ToggleContextPage(context_page) => {
+                if context_page == ContextPage::LaunchLog {
+                    self.launch_log = load_state(LAUNCH_LOG_KEY, CONFIG_VER).unwrap_or_default();
+                }
+
                 if self.context_page == context_page {
                     self.core.window.show_context = !self.core.window.show_context;
                 } else {
@@ -622,21 +1411,62 @@ impl cosmic::Application for AppModel {
                 }
             }
 
+            Message::TogglePreviewProfiles => {
+                self.preview_profiles_expanded = !self.preview_profiles_expanded;
+            }
+
+            Message::PreviewProfile(profile) => {
+                self.preview_profile = profile;
+            }
+
+            Message::PreviewSubmenu(path) => {
+                if let Some(pos) = self.preview_submenus.iter().position(|open| *open == path) {
+                    self.preview_submenus.remove(pos);
+                } else {
+                    self.preview_submenus.push(path);
+                }
+            }
+
+            Message::ClearLaunchLog => {
+                self.launch_log.clear();
+                update_state(LAUNCH_LOG_KEY, CONFIG_VER, &self.launch_log);
+            }
+
             Message::UpdateLogo(logo) => {
+                self.checkpoint();
                 self.selected_logo_name = self.logo_options[logo].clone();
                 self.selected_logo_idx = Some(logo);
+                self.custom_logo_active = false;
+            }
+
+            Message::LogoSearch(query) => {
+                self.logo_search = query;
+            }
 
-                update_config(self.config.clone(), "logo", &self.selected_logo_name);
+            Message::Typing(typing) => {
+                self.typing = typing;
+            }
+
+            Message::ItemFilter(query) => {
+                self.item_filter = query;
+                // focus stays on rows that can be seen
+                if self
+                    .focused
+                    .is_some_and(|i| !self.visible_items().contains(&i))
+                {
+                    self.focused = None;
+                }
+            }
+
+            Message::SelectLogoFile(path) => {
+                self.checkpoint();
+                self.custom_logo_path = path;
+                self.custom_logo_active = true;
             }
 
             Message::ToggleCustomLogo(toggle) => {
+                self.checkpoint();
                 self.custom_logo_active = toggle;
-
-                update_config(
-                    self.config.clone(),
-                    "custom_logo_active",
-                    &self.custom_logo_active,
-                );
             }
 
             Message::UpdateCustomLogo => {
@@ -647,15 +1477,30 @@ impl cosmic::Application for AppModel {
 
                 match file {
                     Some(path) => {
+                        self.checkpoint();
                         let path_string = path.to_str().unwrap_or("");
-                        update_config(self.config.clone(), "custom_logo_path", &path_string);
                         self.custom_logo_path = path_string.to_owned();
                     }
                     None => {}
                 };
             }
 
+            Message::ToggleNotifyOnFailure(toggle) => {
+                self.notify_on_failure = toggle;
+
+                update_config(
+                    self.config.clone(),
+                    "notify_on_failure",
+                    &self.notify_on_failure,
+                );
+            }
+
             Message::AddItem(item_type) => {
+                // ticked rows would no longer line up with the list
+                if self.selecting {
+                    return Task::none();
+                }
+                self.checkpoint();
                 let new_item = MenuItem {
                     item_type: item_type.clone(),
                     label: match &item_type {
@@ -668,8 +1513,12 @@ impl cosmic::Application for AppModel {
                         }
                         _ => None,
                     },
+                    ..Default::default()
                 };
                 self.menu_items.splice(0..0, vec![new_item.clone()]);
+                self.focused = Some(0);
+                // the new item would likely be hidden by the filter
+                self.item_filter.clear();
 
                 if item_type == MenuItemType::LaunchAction {
                     self.dialog_pages
@@ -692,39 +1541,212 @@ impl cosmic::Application for AppModel {
                     .push_front(DialogPage::EditItem(i, menu_item));
             }
 
-            Message::DialogRemoveItem(i) => {
-                self.dialog_pages.push_front(DialogPage::RemoveItem(i));
+            Message::DialogPickApp => {
+                self.dialog_pages
+                    .push_front(DialogPage::PickApp(String::new()));
+                if self.apps.is_none() {
+                    return Task::perform(async { apps::installed() }, |apps| {
+                        cosmic::action::app(Message::Apps(apps))
+                    });
+                }
             }
 
-            Message::SaveItem(i, menu_item) => {
-                self.menu_items[i] = menu_item;
-                self.dialog_pages.pop_front();
+            Message::Apps(apps) => {
+                self.apps = Some(apps);
             }
 
-            Message::RemoveItem(i) => {
-                self.menu_items.remove(i);
+            Message::PickApp(app) => {
                 self.dialog_pages.pop_front();
+                if let Some(DialogPage::EditItem(_, menu_item)) = self.dialog_pages.front_mut() {
+                    menu_item.item_type = MenuItemType::LaunchAction;
+                    menu_item.set_label(app.name);
+                    menu_item.command = Some(app.command);
+                    menu_item.icon = app.icon;
+                }
+            }
+
+            Message::DialogPickIcon => {
+                self.dialog_pages
+                    .push_front(DialogPage::PickIcon(String::new(), false));
+                if self.icons.is_none() {
+                    let theme = cosmic::icon_theme::default();
+                    // walking the theme directories blocks, so keep it off the executor
+                    let scan = async move {
+                        tokio::task::spawn_blocking(move || icons::installed(&theme))
+                            .await
+                            .unwrap_or_default()
+                    };
+                    return Task::perform(scan, |icons| cosmic::action::app(Message::Icons(icons)));
+                }
+            }
+
+            Message::Icons(icons) => {
+                self.icons = Some(icons);
+            }
+
+            Message::PickIcon(icon) => {
+                self.dialog_pages.pop_front();
+                if let Some(DialogPage::EditItem(_, menu_item)) = self.dialog_pages.front_mut() {
+                    menu_item.icon = Some(icon);
+                }
+            }
+
+            Message::PickIconFile => {
+                let file = FileDialog::new()
+                    .add_filter("image", &["svg", "png"])
+                    .set_directory("~/")
+                    .pick_file();
+
+                if let Some(path) = file.as_ref().and_then(|path| path.to_str()) {
+                    return self.update(Message::PickIcon(path.to_owned()));
+                }
+            }
+
+            Message::DialogRemoveItem(i) => {
+                self.dialog_pages.push_front(DialogPage::RemoveItem(i));
+            }
+
+            Message::SaveItem(i, menu_item) => {
+                self.checkpoint();
+                // the item may have been removed elsewhere while editing
+                match self.menu_items.get_mut(i) {
+                    Some(slot) => *slot = menu_item,
+                    None => self.menu_items.push(menu_item),
+                }
+                self.dialog_pages.pop_front();
+            }
+
+            Message::RemoveItem(i) => {
+                self.checkpoint();
+                self.menu_items.remove(i);
+                self.dialog_pages.pop_front();
+                self.clamp_focus();
+                return self.undo_toast(fl!("item-removed"));
+            }
+
+            Message::DuplicateItem(i) => {
+                if let Some(item) = self.menu_items.get(i).cloned() {
+                    self.checkpoint();
+                    self.menu_items.insert(i + 1, item);
+                }
+            }
+
+            Message::Ungroup(i) => {
+                if self.menu_items.get(i).map(MenuItem::item_type) == Some(MenuItemType::Submenu) {
+                    self.checkpoint();
+                    let submenu = self.menu_items.remove(i);
+                    self.menu_items.splice(i..i, submenu.children);
+                }
+            }
+
+            Message::ToggleSelecting => {
+                self.selecting = !self.selecting;
+                self.selected.clear();
+            }
+
+            Message::SelectItem(i, selected) => {
+                if selected {
+                    self.selected.insert(i);
+                } else {
+                    self.selected.remove(&i);
+                }
+            }
+
+            Message::SelectAll => {
+                self.selected = self.visible_items().into_iter().collect();
+            }
+
+            Message::BulkRemove => {
+                if !self.selected.is_empty() {
+                    self.checkpoint();
+                    let (_, removed) = self.take_selected();
+                    self.clamp_focus();
+                    return self.undo_toast(fl!("items-removed", count = removed.len()));
+                }
+            }
+
+            Message::BulkEnable(enabled) => {
+                if !self.selected.is_empty() {
+                    self.checkpoint();
+                    for &i in &self.selected {
+                        self.menu_items[i].enabled = enabled;
+                    }
+                }
+            }
+
+            Message::BulkMove(dir) => {
+                if !self.selected.is_empty() {
+                    self.checkpoint();
+                    let (first, block) = self.take_selected();
+                    let at = match dir {
+                        OrderDirection::Top => 0,
+                        OrderDirection::Up => first.saturating_sub(1),
+                        OrderDirection::Down => (first + 1).min(self.menu_items.len()),
+                        OrderDirection::Bottom => self.menu_items.len(),
+                    };
+                    self.insert_selected(at, block);
+                }
+            }
+
+            Message::GroupSubmenu => {
+                if !self.selected.is_empty() {
+                    self.checkpoint();
+                    let (first, children) = self.take_selected();
+                    let submenu = MenuItem {
+                        item_type: MenuItemType::Submenu,
+                        label: Some(fl!("new-submenu")),
+                        children,
+                        ..Default::default()
+                    };
+                    self.menu_items.insert(first, submenu.clone());
+                    self.selecting = false;
+                    self.dialog_pages
+                        .push_front(DialogPage::EditItem(first, submenu));
+                }
+            }
+
+            Message::GroupHeader => {
+                if !self.selected.is_empty() {
+                    self.checkpoint();
+                    let (first, block) = self.take_selected();
+                    let header = MenuItem {
+                        item_type: MenuItemType::Header,
+                        label: Some(fl!("new-header")),
+                        ..Default::default()
+                    };
+                    self.menu_items.insert(first, header.clone());
+                    self.insert_selected(first + 1, block);
+                    self.dialog_pages
+                        .push_front(DialogPage::EditItem(first, header));
+                }
+            }
+
+            Message::ToggleItem(i, enabled) => {
+                if i < self.menu_items.len() {
+                    self.checkpoint();
+                    self.menu_items[i].enabled = enabled;
+                }
             }
 
             Message::MoveItem(dir, i) => {
+                // while filtered, up and down swap with the next row shown
                 let j = match dir {
-                    OrderDirection::Up => {
-                        if i == 0 {
-                            i
-                        } else {
-                            i - 1
-                        }
+                    OrderDirection::Up | OrderDirection::Down => {
+                        self.neighbour(i, &dir).unwrap_or(i)
                     }
-                    OrderDirection::Down => {
-                        if i == self.menu_items.len() - 1 {
-                            i
-                        } else {
-                            i + 1
+                    OrderDirection::Top | OrderDirection::Bottom => {
+                        self.checkpoint();
+                        let item = self.menu_items.remove(i);
+                        match dir {
+                            OrderDirection::Top => self.menu_items.insert(0, item),
+                            _ => self.menu_items.push(item),
                         }
+                        i
                     }
                 };
 
                 if i != j {
+                    self.checkpoint();
                     let a = self.menu_items[i].clone();
                     let b = self.menu_items[j].clone();
                     self.menu_items[j] = a;
@@ -732,13 +1754,212 @@ impl cosmic::Application for AppModel {
                 }
             }
 
+            Message::FocusItem(i) => {
+                self.focused = Some(i);
+            }
+
+            Message::FocusStep(dir) => {
+                // only rows the filter shows can take focus
+                let visible = self.visible_items();
+                let last = visible.len().checked_sub(1);
+                let at = self
+                    .focused
+                    .and_then(|i| visible.iter().position(|&shown| shown == i));
+                let next = match (at, dir) {
+                    (None, OrderDirection::Up | OrderDirection::Bottom) => last,
+                    (None, _) | (_, OrderDirection::Top) => Some(0),
+                    (Some(at), OrderDirection::Up) => Some(at.saturating_sub(1)),
+                    (Some(at), OrderDirection::Down) => Some(at + 1).min(last),
+                    (_, OrderDirection::Bottom) => last,
+                };
+                self.focused = next.and_then(|at| visible.get(at).copied());
+            }
+
+            Message::EditFocused => {
+                // Enter on a divider has nothing to open
+                let editable = |i: &usize| {
+                    self.menu_items
+                        .get(*i)
+                        .is_some_and(|item| item.item_type() != MenuItemType::Divider)
+                };
+                if let Some(i) = self.focused.filter(editable) {
+                    return self.run_action(WindowAction::EditItem(i));
+                }
+            }
+
+            Message::RemoveFocused => {
+                if self.selecting {
+                    return self.update(Message::BulkRemove);
+                }
+                if let Some(i) = self.focused {
+                    self.dialog_pages.push_front(DialogPage::RemoveItem(i));
+                }
+            }
+
+            Message::MoveFocused(dir) => {
+                if self.selecting {
+                    return self.update(Message::BulkMove(dir));
+                }
+                let rows = self
+                    .focused
+                    .and_then(|i| Some((i, self.neighbour(i, &dir)?)));
+                if let Some((i, j)) = rows {
+                    self.focused = Some(j);
+                    return self.update(Message::MoveItem(dir, i));
+                }
+            }
+
+            Message::Confirm => {
+                if self.dialog_pages.is_empty() {
+                    return self.update(Message::EditFocused);
+                }
+                if let Some(message) = self.dialog_confirm() {
+                    return self.update(message);
+                }
+            }
+
+            Message::Cancel => {
+                if !self.dialog_pages.is_empty() {
+                    // a conflict has to be settled one way or the other
+                    if !matches!(self.dialog_pages.front(), Some(DialogPage::Conflict)) {
+                        self.dialog_pages.pop_front();
+                    }
+                } else if self.selecting {
+                    self.selecting = false;
+                    self.selected.clear();
+                } else if !self.item_filter.is_empty() {
+                    self.item_filter.clear();
+                } else {
+                    self.focused = None;
+                }
+            }
+
+            Message::Search => {
+                let id = match self.dialog_pages.front() {
+                    Some(DialogPage::PickApp(_)) => APP_SEARCH,
+                    Some(DialogPage::PickIcon(..)) => ICON_SEARCH,
+                    _ => {
+                        // focusing from here doesn't report back through on_focus
+                        self.typing = true;
+                        ITEM_SEARCH
+                    }
+                };
+                return widget::text_input::focus(widget::Id::new(id));
+            }
+
+            Message::DragStart(i) => {
+                self.drag = Some((i, i));
+            }
+
+            Message::DragOver(i) => {
+                if let Some((from, _)) = self.drag {
+                    self.drag = Some((from, i));
+                }
+            }
+
+            Message::DragDrop => {
+                if let Some((from, to)) = self.drag.take() {
+                    let len = self.menu_items.len();
+                    if from != to && from < len && to < len {
+                        self.checkpoint();
+                        let item = self.menu_items.remove(from);
+                        self.menu_items.insert(to, item);
+                    }
+                }
+            }
+
+            Message::DragCancel => {
+                self.drag = None;
+            }
+
             Message::DialogResetMenu => {
                 self.dialog_pages.push_front(DialogPage::ResetMenu);
             }
 
             Message::ResetMenu => {
+                self.checkpoint();
                 self.menu_items = MenuItems::default().items;
                 self.dialog_pages.pop_front();
+                return self.undo_toast(fl!("menu-reset"));
+            }
+
+            Message::Undo => {
+                if let Some(snapshot) = self.history.undo(self.snapshot()) {
+                    self.restore(snapshot);
+                    self.schedule_save();
+                }
+            }
+
+            Message::Redo => {
+                if let Some(snapshot) = self.history.redo(self.snapshot()) {
+                    self.restore(snapshot);
+                    self.schedule_save();
+                }
+            }
+
+            Message::Key(modifiers, key) => {
+                let in_dialog = !self.dialog_pages.is_empty();
+                for (key_bind, action) in &self.key_binds {
+                    if self.typing && action.moves_focus() {
+                        continue;
+                    }
+                    if key_bind.matches(modifiers, &key) && (!in_dialog || action.in_dialogs()) {
+                        return self.update(action.message());
+                    }
+                }
+            }
+
+            Message::CloseToast(id) => {
+                self.toasts.remove(id);
+            }
+
+            Message::Save => {
+                self.pending_save = None;
+
+                // someone else wrote the config since we last did
+                let stored = self.stored_snapshot();
+                if stored != self.saved && stored != self.snapshot() {
+                    if !matches!(self.dialog_pages.front(), Some(DialogPage::Conflict)) {
+                        self.dialog_pages.push_front(DialogPage::Conflict);
+                    }
+                } else {
+                    self.persist();
+                }
+            }
+
+            Message::KeepChanges => {
+                self.dialog_pages.pop_front();
+                self.persist();
+            }
+
+            Message::LoadSaved => {
+                self.dialog_pages.pop_front();
+                let current = self.snapshot();
+                self.history.push(current);
+                let stored = self.stored_snapshot();
+                self.adopt(stored);
+            }
+
+            Message::CommandIndex(index) => {
+                self.commands = Some(index);
+                self.command_checks.get_mut().clear();
+            }
+
+            Message::ConfigChanged(config) => {
+                self.notify_on_failure = config.notify_on_failure;
+
+                // our own writes echo back here too
+                let stored = self.snapshot_from(&config);
+                if stored == self.saved {
+                    return Task::none();
+                }
+
+                if self.snapshot() == self.saved || self.snapshot() == stored {
+                    self.pending_save = None;
+                    self.adopt(stored);
+                } else if !matches!(self.dialog_pages.front(), Some(DialogPage::Conflict)) {
+                    self.dialog_pages.push_front(DialogPage::Conflict);
+                }
             }
         }
         Task::none()
@@ -746,6 +1967,462 @@ impl cosmic::Application for AppModel {
 }
 
 impl AppModel {
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            menu_items: self.menu_items.clone(),
+            selected_logo_idx: self.selected_logo_idx,
+            selected_logo_name: self.selected_logo_name.clone(),
+            custom_logo_active: self.custom_logo_active,
+            custom_logo_path: self.custom_logo_path.clone(),
+        }
+    }
+
+    // Problems with a launcher's command, once PATH has been scanned
+    fn command_check(&self, menu_item: &MenuItem) -> Option<CommandCheck> {
+        if menu_item.item_type() != MenuItemType::LaunchAction {
+            return None;
+        }
+        let command = menu_item.command()?;
+        let index = self.commands.as_ref()?;
+        let check = self
+            .command_checks
+            .borrow_mut()
+            .entry(command)
+            .or_insert_with_key(|command| index.check(command))
+            .clone();
+        match check {
+            CommandCheck::Ok => None,
+            check => Some(check),
+        }
+    }
+
+    // The same settings as read back from disk
+    fn stored_snapshot(&self) -> Snapshot {
+        self.snapshot_from(&LogoMenuConfig::load())
+    }
+
+    fn snapshot_from(&self, config: &LogoMenuConfig) -> Snapshot {
+        let selected_logo_name = if IMAGES.contains_key(&config.logo) {
+            config.logo.clone()
+        } else {
+            String::from("Cosmic (Symbolic)")
+        };
+
+        Snapshot {
+            menu_items: config.menu_items.items.clone(),
+            selected_logo_idx: self
+                .logo_options
+                .iter()
+                .position(|n| n == &selected_logo_name),
+            selected_logo_name,
+            custom_logo_active: config.custom_logo_active,
+            custom_logo_path: config.custom_logo_path.clone(),
+        }
+    }
+
+    fn run_action(&mut self, action: WindowAction) -> Task<cosmic::Action<Message>> {
+        match action {
+            WindowAction::EditItem(i) => match self.menu_items.get(i).cloned() {
+                Some(item) if item.item_type() != MenuItemType::Divider => {
+                    self.dialog_pages.push_front(DialogPage::EditItem(i, item));
+                    Task::none()
+                }
+                _ => {
+                    eprintln!(
+                        "cosmic-ext-applet-logomenu ERROR: 'no editable item at position {}'",
+                        i
+                    );
+                    Task::none()
+                }
+            },
+            WindowAction::Preview => {
+                // opened whether or not it was already showing
+                self.context_page = ContextPage::Preview;
+                self.core.window.show_context = true;
+                Task::none()
+            }
+        }
+    }
+
+    // Take on settings from disk, keeping open dialogs pointed at the same items
+    fn adopt(&mut self, stored: Snapshot) {
+        let old_items = std::mem::take(&mut self.menu_items);
+        let find = |i: usize| {
+            let item = old_items.get(i)?;
+            stored.menu_items.iter().position(|other| other == item)
+        };
+
+        self.dialog_pages.retain_mut(|page| match page {
+            // unsaved edits survive, landing at the end if their item is gone
+            DialogPage::EditItem(i, _) => {
+                *i = find(*i).unwrap_or(stored.menu_items.len());
+                true
+            }
+            DialogPage::RemoveItem(i) => match find(*i) {
+                Some(j) => {
+                    *i = j;
+                    true
+                }
+                None => false,
+            },
+            _ => true,
+        });
+
+        self.drag = None;
+        self.restore(stored.clone());
+        self.saved = stored;
+    }
+
+    // Remember the current state before changing it
+    fn checkpoint(&mut self) {
+        // every change to the list goes through here, and a drag would outlive its rows
+        self.drag = None;
+        let snapshot = self.snapshot();
+        self.history.push(snapshot);
+        self.schedule_save();
+    }
+
+    fn schedule_save(&mut self) {
+        self.save_counter += 1;
+        self.pending_save = Some(self.save_counter);
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.menu_items = snapshot.menu_items;
+        self.selected_logo_idx = snapshot.selected_logo_idx;
+        self.selected_logo_name = snapshot.selected_logo_name;
+        self.custom_logo_active = snapshot.custom_logo_active;
+        self.custom_logo_path = snapshot.custom_logo_path;
+        // indices no longer line up with what was ticked
+        self.selected.clear();
+        self.clamp_focus();
+    }
+
+    // Real positions in menu_items of the rows the filter lets through
+    fn visible_items(&self) -> Vec<usize> {
+        let query = self.item_filter.trim().to_lowercase();
+        self.menu_items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| query.is_empty() || item_matches(item, &query))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    // The row shown just above or below, skipping any the filter hides
+    fn neighbour(&self, i: usize, dir: &OrderDirection) -> Option<usize> {
+        let visible = self.visible_items();
+        let at = visible.iter().position(|&shown| shown == i)?;
+        match dir {
+            OrderDirection::Up => at.checked_sub(1).map(|at| visible[at]),
+            OrderDirection::Down => visible.get(at + 1).copied(),
+            OrderDirection::Top | OrderDirection::Bottom => None,
+        }
+    }
+
+    // Keep the focused row pointing at a row that exists, and drop any drag
+    fn clamp_focus(&mut self) {
+        self.drag = None;
+        let last = self.menu_items.len().checked_sub(1);
+        self.focused = self.focused.and_then(|i| Some(i.min(last?)));
+    }
+
+    // What Enter does in the open dialog, if the dialog can be confirmed
+    fn dialog_confirm(&self) -> Option<Message> {
+        match self.dialog_pages.front()? {
+            DialogPage::EditItem(i, menu_item) => {
+                // validation
+                if menu_item.label().unwrap_or_default().is_empty() {
+                    None
+                } else {
+                    Some(Message::SaveItem(*i, menu_item.clone()))
+                }
+            }
+            DialogPage::RemoveItem(i) => Some(Message::RemoveItem(*i)),
+            DialogPage::ResetMenu => Some(Message::ResetMenu),
+            DialogPage::Conflict => Some(Message::KeepChanges),
+            // picked by clicking a result
+            DialogPage::PickApp(_) | DialogPage::PickIcon(..) => None,
+        }
+    }
+
+    // Pull the ticked items out of the menu, returning where the first one was
+    fn take_selected(&mut self) -> (usize, Vec<MenuItem>) {
+        let selected = std::mem::take(&mut self.selected);
+        let first = selected.first().copied().unwrap_or(0);
+        let mut block = Vec::with_capacity(selected.len());
+        for &i in selected.iter().rev() {
+            if i < self.menu_items.len() {
+                block.push(self.menu_items.remove(i));
+            }
+        }
+        block.reverse();
+        (first, block)
+    }
+
+    // Put a block back in one piece, keeping it ticked
+    fn insert_selected(&mut self, at: usize, block: Vec<MenuItem>) {
+        let at = at.min(self.menu_items.len());
+        self.selected = (at..at + block.len()).collect();
+        self.menu_items.splice(at..at, block);
+    }
+
+    // Write whatever changed since the last save in a single transaction
+    fn persist(&mut self) {
+        let current = self.snapshot();
+        if current == self.saved {
+            return;
+        }
+
+        let tx = self.config.transaction();
+        if current.selected_logo_name != self.saved.selected_logo_name {
+            let _ = tx.set("logo", &current.selected_logo_name);
+        }
+        if current.custom_logo_active != self.saved.custom_logo_active {
+            let _ = tx.set("custom_logo_active", current.custom_logo_active);
+        }
+        if current.custom_logo_path != self.saved.custom_logo_path {
+            let _ = tx.set("custom_logo_path", &current.custom_logo_path);
+        }
+        if current.menu_items != self.saved.menu_items {
+            let _ = tx.set(
+                "menu_items",
+                MenuItems {
+                    items: current.menu_items.clone(),
+                },
+            );
+        }
+
+        match tx.commit() {
+            Ok(()) => self.saved = current,
+            Err(e) => eprintln!("cosmic-ext-applet-logomenu ERROR: '{}'", e),
+        }
+    }
+
+    fn undo_toast(&mut self, text: String) -> Task<cosmic::Action<Message>> {
+        self.toasts
+            .push(widget::toaster::Toast::new(text).action(fl!("undo"), |_| Message::Undo))
+            .map(cosmic::Action::App)
+    }
+
+    // Bulk actions shown in place of the add buttons while selecting
+    fn selection_bar(&self) -> Element<'_, Message> {
+        let any = !self.selected.is_empty();
+        let when_any = |message: Message| any.then_some(message);
+        let move_button = |icon: &'static str, dir: OrderDirection| {
+            widget::button::icon(widget::icon::from_name(icon))
+                .on_press_maybe(when_any(Message::BulkMove(dir)))
+        };
+
+        widget::column()
+            .push(
+                widget::row()
+                    .push(
+                        widget::text::body(fl!("selected-count", count = self.selected.len()))
+                            .width(Length::Fill),
+                    )
+                    .push(widget::button::text(fl!("select-all")).on_press(Message::SelectAll))
+                    .push(widget::button::suggested(fl!("done")).on_press(Message::ToggleSelecting))
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+            )
+            .push(
+                widget::flex_row(vec![
+                    widget::row::with_capacity(4)
+                        .push(move_button("go-top-symbolic", OrderDirection::Top))
+                        .push(move_button("pan-up-symbolic", OrderDirection::Up))
+                        .push(move_button("pan-down-symbolic", OrderDirection::Down))
+                        .push(move_button("go-bottom-symbolic", OrderDirection::Bottom))
+                        .into(),
+                    widget::button::standard(fl!("enable"))
+                        .on_press_maybe(when_any(Message::BulkEnable(true)))
+                        .into(),
+                    widget::button::standard(fl!("disable"))
+                        .on_press_maybe(when_any(Message::BulkEnable(false)))
+                        .into(),
+                    widget::button::standard(fl!("group-submenu"))
+                        .on_press_maybe(when_any(Message::GroupSubmenu))
+                        .into(),
+                    widget::button::standard(fl!("group-header"))
+                        .on_press_maybe(when_any(Message::GroupHeader))
+                        .into(),
+                    widget::button::destructive(fl!("remove"))
+                        .on_press_maybe(when_any(Message::BulkRemove))
+                        .into(),
+                ])
+                .row_spacing(10)
+                .column_spacing(10),
+            )
+            .spacing(10)
+            .into()
+    }
+
+    // Built in logos grouped by distro, then installed packs and the custom logo
+    fn logo_gallery(&self) -> Element<'_, Message> {
+        let query = &self.logo_search;
+        let mut tiles: Vec<Element<'_, Message>> = Vec::new();
+
+        for group in self.logo_groups.iter().filter(|group| group.matches(query)) {
+            let mut variants = widget::row().spacing(4);
+            for key in &group.variants {
+                let (bytes, symbolic) = IMAGES[key.as_str()];
+                let selected = !self.custom_logo_active && &self.selected_logo_name == key;
+                let message = self
+                    .logo_options
+                    .iter()
+                    .position(|option| option == key)
+                    .map(Message::UpdateLogo);
+                variants = variants.push(widget::tooltip(
+                    logo_button(
+                        widget::svg::Handle::from_memory(bytes),
+                        symbolic,
+                        selected,
+                        message,
+                    ),
+                    widget::text(logos::variant_name(key).unwrap_or(&group.name).to_string()),
+                    widget::tooltip::Position::Bottom,
+                ));
+            }
+            tiles.push(logo_tile(group.name.clone(), variants));
+        }
+
+        // the custom logo gets a tile unless a pack tile already stands for it
+        let custom_in_packs = self
+            .logo_packs
+            .iter()
+            .any(|logo| logo.path == Path::new(&self.custom_logo_path));
+        let custom = (!self.custom_logo_path.is_empty() && !custom_in_packs).then(|| PackLogo {
+            name: fl!("custom-logo"),
+            path: self.custom_logo_path.clone().into(),
+        });
+
+        for logo in self
+            .logo_packs
+            .iter()
+            .chain(custom.iter())
+            .filter(|logo| logo.matches(query))
+        {
+            let path = logo.path.to_string_lossy().into_owned();
+            let selected = self.custom_logo_active && self.custom_logo_path == path;
+            let button = logo_button(
+                widget::svg::Handle::from_path(&logo.path),
+                false,
+                selected,
+                Some(Message::SelectLogoFile(path)),
+            );
+            tiles.push(logo_tile(logo.name.clone(), widget::row().push(button)));
+        }
+
+        settings::section()
+            .title(fl!("logo"))
+            .add(
+                widget::column()
+                    .push(
+                        widget::search_input(fl!("search-logos"), query.clone())
+                            .id(widget::Id::new(LOGO_SEARCH))
+                            .on_input(Message::LogoSearch)
+                            .on_focus(Message::Typing(true))
+                            .on_unfocus(Message::Typing(false)),
+                    )
+                    .push(widget::flex_row(tiles).row_spacing(10).column_spacing(10))
+                    .spacing(10),
+            )
+            .into()
+    }
+
+    // The popup as the applet draws it, where pressing an item edits it
+    fn preview(&self) -> Element<'_, Message> {
+        let power_profiles = [
+            String::from("power-saver"),
+            String::from("balanced"),
+            String::from("performance"),
+        ];
+        let content_list = view::menu_items(
+            view::popup_column(),
+            &self.menu_items,
+            Some(view::PowerProfilesView {
+                active: &self.preview_profile,
+                available: &power_profiles,
+                expanded: self.preview_profiles_expanded,
+            }),
+            &self.preview_submenus,
+            view::MenuMessages {
+                // items inside submenus report the submenu, which can't be edited from here
+                item: |i, item: &MenuItem| {
+                    let top_level = self.menu_items.get(i) == Some(item);
+                    top_level.then(|| Message::DialogEditItem(i, item.clone()))
+                },
+                toggle_profiles: Message::TogglePreviewProfiles,
+                set_profile: |profile: &str| Message::PreviewProfile(profile.to_owned()),
+                toggle_submenu: Message::PreviewSubmenu,
+            },
+        );
+
+        container(content_list)
+            .class(theme::Container::Dropdown)
+            .width(Length::Fixed(view::POPUP_MIN_WIDTH))
+            .apply(container)
+            .center_x(Length::Fill)
+            .into()
+    }
+
+    fn launch_log_view(&self) -> Element<'_, Message> {
+        if self.launch_log.is_empty() {
+            return widget::text::body(fl!("launch-log-empty")).into();
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        // newest first
+        let mut records = widget::column().spacing(theme::active().cosmic().space_s());
+        for record in self.launch_log.iter().rev() {
+            let status = match record.exit_code {
+                Some(0) => fl!("exited-successfully"),
+                Some(code) => fl!("exited-with-code", code = code),
+                None => fl!("exited-by-signal"),
+            };
+            let summary = format!(
+                "{}  ·  {:.1} s  ·  {}",
+                status,
+                record.duration_ms as f64 / 1000.0,
+                fl!(
+                    "launched-ago",
+                    time = format_elapsed(now.saturating_sub(record.started))
+                ),
+            );
+
+            let mut details = widget::column()
+                .push(widget::text::caption(record.command.clone()))
+                .push(widget::text::caption(summary));
+            if !record.stderr.is_empty() {
+                details = details.push(
+                    container(widget::text::monotext(record.stderr.join("\n")))
+                        .padding([5, 0, 0, 0]),
+                );
+            }
+
+            records = records.push(settings::section().add(settings::item_row(vec![
+                        widget::icon::from_name(if record.success() {
+                            "emblem-ok-symbolic"
+                        } else {
+                            "dialog-warning-symbolic"
+                        })
+                        .size(16)
+                        .into(),
+                        widget::column()
+                            .push(widget::text::heading(record.label.clone()))
+                            .push(details)
+                            .width(Length::Fill)
+                            .into(),
+                    ])));
+        }
+
+        records.into()
+    }
+
     pub fn update_title(&mut self) -> Task<cosmic::Action<Message>> {
         if let Some(id) = self.core.main_window_id() {
             self.set_window_title(fl!("app-title"), id)
@@ -759,11 +2436,42 @@ impl AppModel {
 pub enum ContextPage {
     #[default]
     About,
+    LaunchLog,
+    Preview,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum MenuAction {
     About,
+    LaunchLog,
+    Preview,
+    Undo,
+    Redo,
+    AddItem,
+    AddDivider,
+    EditItem,
+    RemoveItem,
+    MoveUp,
+    MoveDown,
+    PreviousItem,
+    NextItem,
+    Search,
+    Cancel,
+}
+
+impl MenuAction {
+    // Enter and Escape answer the open dialog, the rest wait until it closes
+    fn in_dialogs(&self) -> bool {
+        matches!(
+            self,
+            MenuAction::EditItem | MenuAction::Cancel | MenuAction::Search
+        )
+    }
+
+    // Up and Down, which a focused search box needs for itself
+    fn moves_focus(&self) -> bool {
+        matches!(self, MenuAction::PreviousItem | MenuAction::NextItem)
+    }
 }
 
 impl menu::action::MenuAction for MenuAction {
@@ -772,10 +2480,203 @@ impl menu::action::MenuAction for MenuAction {
     fn message(&self) -> Self::Message {
         match self {
             MenuAction::About => Message::ToggleContextPage(ContextPage::About),
+            MenuAction::LaunchLog => Message::ToggleContextPage(ContextPage::LaunchLog),
+            MenuAction::Preview => Message::ToggleContextPage(ContextPage::Preview),
+            MenuAction::Undo => Message::Undo,
+            MenuAction::Redo => Message::Redo,
+            MenuAction::AddItem => Message::AddItem(MenuItemType::LaunchAction),
+            MenuAction::AddDivider => Message::AddItem(MenuItemType::Divider),
+            MenuAction::EditItem => Message::Confirm,
+            MenuAction::RemoveItem => Message::RemoveFocused,
+            MenuAction::MoveUp => Message::MoveFocused(OrderDirection::Up),
+            MenuAction::MoveDown => Message::MoveFocused(OrderDirection::Down),
+            MenuAction::PreviousItem => Message::FocusStep(OrderDirection::Up),
+            MenuAction::NextItem => Message::FocusStep(OrderDirection::Down),
+            MenuAction::Search => Message::Search,
+            MenuAction::Cancel => Message::Cancel,
+        }
+    }
+}
+
+// Whether the item's label, command or type, or anything in its submenu,
+// contains the lowercased query
+fn item_matches(item: &MenuItem, query: &str) -> bool {
+    [
+        item.label(),
+        item.command(),
+        Some(item.item_type().as_localized_string()),
+    ]
+    .into_iter()
+    .flatten()
+    .any(|field| field.to_lowercase().contains(query))
+        || item.children.iter().any(|child| item_matches(child, query))
+}
+
+// Text with every match of the filter picked out in the accent colour
+fn highlighted<'a>(text: String, query: &str) -> Element<'a, Message> {
+    let query = query.trim().to_lowercase();
+    let lower = text.to_lowercase();
+    // lowercasing outside ASCII can shift positions, so those stay plain
+    if query.is_empty() || lower.len() != text.len() {
+        return widget::text(text).into();
+    }
+
+    let accent = theme::active().cosmic().accent_color();
+    let accent = cosmic::iced::Color::from_rgb(accent.red, accent.green, accent.blue);
+    let mut spans: Vec<Span<'a, (), Font>> = Vec::new();
+    let mut start = 0;
+    for (at, _) in lower.match_indices(&query) {
+        let end = at + query.len();
+        if at < start || !text.is_char_boundary(at) || !text.is_char_boundary(end) {
+            continue;
+        }
+        spans.push(span(text[start..at].to_string()));
+        spans.push(
+            span(text[at..end].to_string())
+                .color(accent)
+                .font(cosmic::font::bold()),
+        );
+        start = end;
+    }
+    spans.push(span(text[start..].to_string()));
+
+    rich_text(spans).into()
+}
+
+// Rough "how long ago" for the launch log
+fn format_elapsed(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{} s", secs),
+        60..3600 => format!("{} min", secs / 60),
+        3600..86400 => format!("{} h", secs / 3600),
+        _ => format!("{} d", secs / 86400),
+    }
+}
+
+// Labelled number input that ignores anything it can't parse or won't accept
+fn resource_input<'a, T>(
+    label: String,
+    value: Option<T>,
+    valid: impl Fn(T) -> bool + 'a,
+    on_change: impl Fn(Option<T>) -> Message + 'a,
+) -> Element<'a, Message>
+where
+    T: std::str::FromStr + ToString + Copy + 'a,
+{
+    widget::container(
+        widget::row::with_capacity(2)
+            .push(
+                widget::text(label)
+                    .align_y(Alignment::Center)
+                    .height(30)
+                    .width(120),
+            )
+            .push(
+                widget::text_input(
+                    fl!("unlimited"),
+                    value.map(|v| v.to_string()).unwrap_or_default(),
+                )
+                .on_input(move |input| {
+                    if input.trim().is_empty() {
+                        on_change(None)
+                    } else {
+                        let parsed = input.trim().parse().ok().filter(|v| valid(*v));
+                        on_change(parsed.or(value))
+                    }
+                })
+                .width(Length::Fill),
+            ),
+    )
+    .into()
+}
+
+fn logo_button<'a>(
+    handle: widget::svg::Handle,
+    symbolic: bool,
+    selected: bool,
+    message: Option<Message>,
+) -> Element<'a, Message> {
+    widget::button::custom(widget::svg(handle).symbolic(symbolic).width(40).height(40))
+        .class(theme::Button::Image)
+        .selected(selected)
+        .padding(6)
+        .on_press_maybe(message)
+        .into()
+}
+
+fn logo_tile<'a>(name: String, variants: widget::Row<'a, Message>) -> Element<'a, Message> {
+    widget::column()
+        .push(variants)
+        .push(widget::text::caption(name))
+        .spacing(4)
+        .width(Length::Fixed(112.0))
+        .align_x(Alignment::Center)
+        .into()
+}
+
+fn check_message(check: &CommandCheck) -> String {
+    match check {
+        CommandCheck::Ok => String::new(),
+        CommandCheck::Invalid => fl!("command-invalid"),
+        CommandCheck::NotFound(program, _) => fl!("command-not-found", program = program.as_str()),
+        CommandCheck::NotExecutable(program) => {
+            fl!("command-not-executable", program = program.as_str())
         }
     }
 }
 
+pub fn key_binds() -> HashMap<menu::KeyBind, MenuAction> {
+    use menu::key_bind::Modifier;
+
+    let mut key_binds = HashMap::new();
+    let mut bind = |modifiers: Vec<Modifier>, key: Key, action: MenuAction| {
+        key_binds.insert(menu::KeyBind { modifiers, key }, action);
+    };
+
+    bind(
+        vec![Modifier::Ctrl],
+        Key::Character("z".into()),
+        MenuAction::Undo,
+    );
+    bind(
+        vec![Modifier::Ctrl, Modifier::Shift],
+        Key::Character("z".into()),
+        MenuAction::Redo,
+    );
+    bind(
+        vec![Modifier::Ctrl],
+        Key::Character("n".into()),
+        MenuAction::AddItem,
+    );
+    bind(
+        vec![Modifier::Ctrl, Modifier::Shift],
+        Key::Character("n".into()),
+        MenuAction::AddDivider,
+    );
+    bind(vec![], Key::Named(Named::Enter), MenuAction::EditItem);
+    bind(vec![], Key::Named(Named::Delete), MenuAction::RemoveItem);
+    bind(
+        vec![Modifier::Alt],
+        Key::Named(Named::ArrowUp),
+        MenuAction::MoveUp,
+    );
+    bind(
+        vec![Modifier::Alt],
+        Key::Named(Named::ArrowDown),
+        MenuAction::MoveDown,
+    );
+    bind(vec![], Key::Named(Named::ArrowUp), MenuAction::PreviousItem);
+    bind(vec![], Key::Named(Named::ArrowDown), MenuAction::NextItem);
+    bind(
+        vec![Modifier::Ctrl],
+        Key::Character("f".into()),
+        MenuAction::Search,
+    );
+    bind(vec![], Key::Named(Named::Escape), MenuAction::Cancel);
+
+    key_binds
+}
+
 pub fn get_menu_items() -> Vec<MenuItem> {
     let mut display_items = Vec::new();
 
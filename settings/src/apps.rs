@@ -0,0 +1,232 @@
+// SPDX-License-Identifier: GPL-3.0-only
+//
+// Installed applications, read from their desktop entries
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+pub struct DesktopApp {
+    pub id: String,
+    pub name: String,
+    pub command: String,
+    pub icon: Option<String>,
+}
+
+impl DesktopApp {
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.name.to_lowercase().contains(&query)
+            || self.id.to_lowercase().contains(&query)
+            || self.command.to_lowercase().contains(&query)
+    }
+}
+
+// Every launchable app, sorted by name
+pub fn installed() -> Vec<DesktopApp> {
+    let languages = languages();
+    let mut seen = HashSet::new();
+    let mut apps = Vec::new();
+
+    for dir in application_dirs() {
+        for path in desktop_files(&dir) {
+            let Some(id) = desktop_id(&dir, &path) else {
+                continue;
+            };
+            // the first entry with an id wins, even if it hides the app
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+            if let Some(app) = parse(&path, id, &languages) {
+                apps.push(app);
+            }
+        }
+    }
+
+    apps.sort_by_key(|app| app.name.to_lowercase());
+    apps
+}
+
+// Highest priority first, following the XDG base directory spec
+fn application_dirs() -> Vec<PathBuf> {
+    let mut data_dirs = Vec::new();
+
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    if let Some(data_home) = std::env::var_os("XDG_DATA_HOME") {
+        data_dirs.push(PathBuf::from(data_home));
+    }
+    // inside the sandbox XDG_DATA_HOME points at the app's own directory
+    if let Some(home) = &home {
+        data_dirs.push(home.join(".local/share"));
+        data_dirs.push(home.join(".local/share/flatpak/exports/share"));
+    }
+    data_dirs.push(PathBuf::from("/var/lib/flatpak/exports/share"));
+
+    if liblog::is_flatpak() {
+        data_dirs.push(PathBuf::from("/run/host/usr/local/share"));
+        data_dirs.push(PathBuf::from("/run/host/usr/share"));
+    } else {
+        let system = std::env::var("XDG_DATA_DIRS")
+            .ok()
+            .filter(|dirs| !dirs.is_empty())
+            .unwrap_or_else(|| String::from("/usr/local/share:/usr/share"));
+        data_dirs.extend(system.split(':').map(PathBuf::from));
+    }
+
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for dir in data_dirs {
+        let dir = dir.join("applications");
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
+
+fn desktop_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return files;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(desktop_files(&path));
+        } else if path.extension().is_some_and(|ext| ext == "desktop") {
+            files.push(path);
+        }
+    }
+    files
+}
+
+// e.g. applications/kde4/foo.desktop is known as kde4-foo.desktop
+fn desktop_id(dir: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(dir).ok()?;
+    Some(relative.to_string_lossy().replace('/', "-"))
+}
+
+fn parse(path: &Path, id: String, languages: &[String]) -> Option<DesktopApp> {
+    let contents = fs::read_to_string(path).ok()?;
+
+    let mut fields = Vec::new();
+    let mut in_entry = false;
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+        } else if in_entry && !line.starts_with('#') {
+            if let Some((key, value)) = line.split_once('=') {
+                fields.push((key.trim(), value.trim()));
+            }
+        }
+    }
+    let field = |key: &str| {
+        fields
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| *value)
+    };
+
+    if field("Type") != Some("Application")
+        || field("NoDisplay") == Some("true")
+        || field("Hidden") == Some("true")
+    {
+        return None;
+    }
+
+    let name = languages
+        .iter()
+        .find_map(|lang| field(&format!("Name[{}]", lang)))
+        .or_else(|| field("Name"))?;
+    let command = exec_command(field("Exec")?)?;
+
+    Some(DesktopApp {
+        id,
+        name: name.to_string(),
+        command,
+        icon: field("Icon")
+            .filter(|icon| !icon.is_empty())
+            .map(String::from),
+    })
+}
+
+// Exec lines have their own quoting and carry %u style placeholders for files
+// and urls, which we never pass, so the arguments are unpicked and quoted again
+// for the shell the applet runs them with
+fn exec_command(exec: &str) -> Option<String> {
+    let words = shell_words::split(&unescape(exec)).ok()?;
+    let words: Vec<String> = words
+        .iter()
+        .filter_map(|word| strip_field_codes(word))
+        .collect();
+    (!words.is_empty()).then(|| shell_words::join(words))
+}
+
+// Drops the field codes from an argument, and the argument itself if that
+// leaves nothing of it
+fn strip_field_codes(word: &str) -> Option<String> {
+    // flatpak's markers around the file arguments
+    if matches!(word, "@@" | "@@u" | "@@f") {
+        return None;
+    }
+
+    let mut stripped = String::with_capacity(word.len());
+    let mut had_code = false;
+    let mut chars = word.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            stripped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('%') => stripped.push('%'),
+            _ => had_code = true,
+        }
+    }
+    (!had_code || !stripped.is_empty()).then_some(stripped)
+}
+
+// The escapes any string value in a desktop entry can use, undone before the
+// Exec quoting is
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some('\\') => unescaped.push('\\'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+// Locale names to look for in Name[...] keys, most specific first
+fn languages() -> Vec<String> {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|value| !value.is_empty()))
+        .unwrap_or_default();
+
+    // sv_SE.UTF-8@euro -> sv_SE, sv
+    let locale = locale.split(['.', '@']).next().unwrap_or_default();
+    let mut languages = vec![locale.to_string()];
+    if let Some((lang, _country)) = locale.split_once('_') {
+        languages.push(lang.to_string());
+    }
+    languages.retain(|lang| !lang.is_empty() && lang != "C");
+    languages
+}
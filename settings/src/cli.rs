@@ -0,0 +1,338 @@
+// SPDX-License-Identifier: GPL-3.0-only
+//
+// Headless menu editing, for provisioning machines without opening the window
+
+use crate::app::WindowAction;
+use cosmic::cosmic_config::{ConfigSet, CosmicConfigEntry};
+use liblog::{IMAGES, LogoMenuConfig, MenuError, MenuItem, MenuItemType, MenuItems};
+use serde_json::json;
+use std::fmt;
+use std::io::Read;
+
+const COMMANDS: [&str; 8] = [
+    "list", "add", "remove", "move", "set-logo", "export", "import", "reset",
+];
+
+const USAGE: &str = "Usage: cosmic-ext-logomenu-settings <command> [--json]
+
+Commands:
+  list                                         Show the menu items
+  add --type TYPE [--label L] [--command C] [--at N]
+                                               Add an item (TYPE is launch, power,
+                                               power-profiles, divider or header)
+  remove N                                     Remove the item at position N
+  move N M                                     Move the item at position N to M
+  set-logo NAME                                Use one of the built in logos
+  export                                       Print the whole config
+  import [FILE]                                Replace the config from FILE or stdin
+  reset                                        Restore the default menu
+
+Exports and imports are RON, or JSON with --json.
+
+Window options:
+  --edit-item N                                Open the edit dialog for item N
+  --preview                                    Show the popup preview
+
+If the window is already open these are passed on to it.";
+
+enum CliError {
+    Usage(String),
+    Invalid(String),
+    Config(String),
+}
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(e) => write!(f, "{}\n\n{}", e, USAGE),
+            CliError::Invalid(e) | CliError::Config(e) => write!(f, "{}", e),
+        }
+    }
+}
+impl From<MenuError> for CliError {
+    fn from(e: MenuError) -> Self {
+        CliError::Invalid(e.to_string())
+    }
+}
+
+// Whether the arguments ask for a command rather than the window
+pub fn is_command(args: &[String]) -> bool {
+    // flags like --json can come before the command
+    args.iter().any(|arg| arg == "--help")
+        || args
+            .iter()
+            .find(|arg| !arg.starts_with("--"))
+            .is_some_and(|arg| COMMANDS.contains(&arg.as_str()))
+}
+
+// What the window should do once open, going by the options it was launched with
+pub fn window_action(args: &[String]) -> Result<Option<WindowAction>, String> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        [] => Ok(None),
+        ["--edit-item", i] => parse_index(i)
+            .map(|i| Some(WindowAction::EditItem(i)))
+            .map_err(|e| e.to_string()),
+        ["--preview"] => Ok(Some(WindowAction::Preview)),
+        _ => Err(CliError::Usage(format!("unknown options '{}'", args.join(" "))).to_string()),
+    }
+}
+
+// Run a command and return the process exit code
+pub fn run(args: &[String]) -> i32 {
+    let json = args.iter().any(|arg| arg == "--json");
+    let args: Vec<&str> = args
+        .iter()
+        .map(String::as_str)
+        .filter(|arg| *arg != "--json")
+        .collect();
+
+    match run_command(&args, json) {
+        Ok(output) => {
+            if !output.is_empty() {
+                println!("{}", output);
+            }
+            0
+        }
+        Err(e) => {
+            if json {
+                println!("{}", json!({ "error": error_message(&e) }));
+            } else {
+                eprintln!("{}", e);
+            }
+            match e {
+                CliError::Usage(_) => 2,
+                CliError::Invalid(_) | CliError::Config(_) => 1,
+            }
+        }
+    }
+}
+
+fn error_message(e: &CliError) -> String {
+    match e {
+        CliError::Usage(e) | CliError::Invalid(e) | CliError::Config(e) => e.clone(),
+    }
+}
+
+fn run_command(args: &[&str], json: bool) -> Result<String, CliError> {
+    let mut config = LogoMenuConfig::load();
+
+    match args {
+        ["--help"] => Ok(USAGE.to_owned()),
+        ["list"] => Ok(list(&config.menu_items, json)),
+        ["add", options @ ..] => {
+            let (item, at) = parse_add(options)?;
+            let at = config.menu_items.insert(at, item)?;
+            save_menu(&config.menu_items)?;
+            Ok(done(json, json!({ "added": at })))
+        }
+        ["remove", i] => {
+            config.menu_items.remove(parse_index(i)?)?;
+            save_menu(&config.menu_items)?;
+            Ok(done(json, json!({ "removed": parse_index(i)? })))
+        }
+        ["move", from, to] => {
+            config
+                .menu_items
+                .move_item(parse_index(from)?, parse_index(to)?)?;
+            save_menu(&config.menu_items)?;
+            Ok(done(
+                json,
+                json!({ "moved": [parse_index(from)?, parse_index(to)?] }),
+            ))
+        }
+        ["set-logo", name] => {
+            let logo = IMAGES
+                .keys()
+                .find(|logo| logo.eq_ignore_ascii_case(name))
+                .ok_or_else(|| CliError::Invalid(format!("'{}' is not a known logo", name)))?;
+            let helper = LogoMenuConfig::config().map_err(config_error)?;
+            helper.set("logo", *logo).map_err(config_error)?;
+            helper
+                .set("custom_logo_active", false)
+                .map_err(config_error)?;
+            Ok(done(json, json!({ "logo": logo })))
+        }
+        ["export"] => {
+            if json {
+                serde_json::to_string_pretty(&config).map_err(|e| CliError::Config(e.to_string()))
+            } else {
+                ron::ser::to_string_pretty(&config, ron::ser::PrettyConfig::default())
+                    .map_err(|e| CliError::Config(e.to_string()))
+            }
+        }
+        ["import", source @ ..] => {
+            let input = match source {
+                [] => {
+                    let mut input = String::new();
+                    std::io::stdin()
+                        .read_to_string(&mut input)
+                        .map_err(|e| CliError::Config(e.to_string()))?;
+                    input
+                }
+                [path] => std::fs::read_to_string(path)
+                    .map_err(|e| CliError::Config(format!("{}: {}", path, e)))?,
+                _ => return Err(CliError::Usage("import takes at most one file".to_owned())),
+            };
+            let imported = parse_config(&input, json)?;
+            for item in &imported.menu_items.items {
+                item.validate()?;
+            }
+            let helper = LogoMenuConfig::config().map_err(config_error)?;
+            imported.write_entry(&helper).map_err(config_error)?;
+            Ok(done(
+                json,
+                json!({ "imported": imported.menu_items.items.len() }),
+            ))
+        }
+        ["reset"] => {
+            save_menu(&MenuItems::default())?;
+            Ok(done(json, json!({ "reset": true })))
+        }
+        [] => Err(CliError::Usage("no command given".to_owned())),
+        [command, ..] => Err(CliError::Usage(format!(
+            "wrong arguments for '{}'",
+            command
+        ))),
+    }
+}
+
+fn list(menu_items: &MenuItems, json: bool) -> String {
+    if json {
+        let items: Vec<_> = menu_items
+            .items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                json!({
+                    "index": i,
+                    "type": type_name(item.item_type()),
+                    "label": item.label(),
+                    "command": item.command(),
+                    "icon": item.icon(),
+                    "enabled": item.enabled,
+                })
+            })
+            .collect();
+        return serde_json::Value::from(items).to_string();
+    }
+
+    menu_items
+        .items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| match item.item_type() {
+            MenuItemType::Divider => format!("{:>3}  ----{}", i, disabled_note(item)),
+            item_type => format!(
+                "{:>3}  {:<15} {}   ::   {}{}",
+                i,
+                type_name(item_type),
+                item.label().unwrap_or_default(),
+                item.command().unwrap_or_default(),
+                disabled_note(item)
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn disabled_note(item: &MenuItem) -> &'static str {
+    if item.enabled { "" } else { "   (disabled)" }
+}
+
+fn parse_add(options: &[&str]) -> Result<(MenuItem, Option<usize>), CliError> {
+    let mut item = MenuItem::default();
+    let mut item_type = None;
+    let mut at = None;
+
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let value = options
+            .next()
+            .ok_or_else(|| CliError::Usage(format!("{} needs a value", option)))?;
+        match *option {
+            "--type" => item_type = Some(parse_type(value)?),
+            "--label" => item.set_label(value.to_string()),
+            "--command" => item.command = Some(value.to_string()),
+            "--at" => at = Some(parse_index(value)?),
+            _ => return Err(CliError::Usage(format!("unknown option '{}'", option))),
+        }
+    }
+
+    item.item_type = item_type.ok_or_else(|| CliError::Usage("--type is required".to_owned()))?;
+    Ok((item, at))
+}
+
+fn parse_type(value: &str) -> Result<MenuItemType, CliError> {
+    match value {
+        "launch" => Ok(MenuItemType::LaunchAction),
+        "power" => Ok(MenuItemType::PowerAction),
+        "power-profiles" => Ok(MenuItemType::PowerProfiles),
+        "divider" => Ok(MenuItemType::Divider),
+        "header" => Ok(MenuItemType::Header),
+        _ => Err(CliError::Usage(format!("unknown item type '{}'", value))),
+    }
+}
+
+fn type_name(item_type: MenuItemType) -> &'static str {
+    match item_type {
+        MenuItemType::LaunchAction => "launch",
+        MenuItemType::PowerAction => "power",
+        MenuItemType::PowerProfiles => "power-profiles",
+        MenuItemType::Divider => "divider",
+        MenuItemType::Header => "header",
+        MenuItemType::Submenu => "submenu",
+    }
+}
+
+fn parse_index(value: &str) -> Result<usize, CliError> {
+    value
+        .parse()
+        .map_err(|_| CliError::Usage(format!("'{}' is not a position", value)))
+}
+
+fn save_menu(menu_items: &MenuItems) -> Result<(), CliError> {
+    LogoMenuConfig::config()
+        .map_err(config_error)?
+        .set("menu_items", menu_items)
+        .map_err(config_error)
+}
+
+fn parse_config(input: &str, json: bool) -> Result<LogoMenuConfig, CliError> {
+    if json {
+        serde_json::from_str(input).map_err(|e| CliError::Invalid(e.to_string()))
+    } else {
+        ron::from_str(input).map_err(|e| CliError::Invalid(e.to_string()))
+    }
+}
+
+fn config_error(e: impl fmt::Display) -> CliError {
+    CliError::Config(e.to_string())
+}
+
+fn done(json: bool, value: serde_json::Value) -> String {
+    if json {
+        value.to_string()
+    } else {
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn import_fills_in_missing_fields() {
+        let expected = LogoMenuConfig {
+            logo: String::from("Fedora"),
+            ..LogoMenuConfig::default()
+        };
+        for (input, json) in [
+            (r#"(logo: "Fedora")"#, false),
+            (r#"{"logo": "Fedora"}"#, true),
+        ] {
+            let imported = parse_config(input, json).unwrap_or_else(|e| panic!("{}", e));
+            assert_eq!(imported, expected);
+        }
+    }
+}
@@ -0,0 +1,246 @@
+// SPDX-License-Identifier: GPL-3.0-only
+//
+// Checks that launcher commands point at something that can actually run
+
+use cosmic::iced::futures::channel::oneshot;
+use liblog::flatpak;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::os::fd::OwnedFd;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::thread;
+
+const MAX_SUGGESTIONS: usize = 3;
+// More than any real PATH lists, so a runaway scan gets stopped
+const MAX_SCAN_OUTPUT: u64 = 16 * 1024 * 1024;
+
+// Lists every file on the host's PATH, marking executables with x
+const HOST_SCAN: &str = r#"IFS=:
+for dir in $PATH; do
+    for file in "$dir"/*; do
+        if [ -x "$file" ]; then echo "x $file"; elif [ -f "$file" ]; then echo "- $file"; fi
+    done
+done"#;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandCheck {
+    Ok,
+    // the command line itself doesn't parse, e.g. an unclosed quote
+    Invalid,
+    NotFound(String, Vec<String>),
+    NotExecutable(String),
+}
+
+// Programs found on PATH, by name, and whether they can be run
+#[derive(Debug, Default)]
+pub struct CommandIndex {
+    programs: HashMap<String, bool>,
+    // absolute paths can only be checked when they are in our own filesystem
+    host: bool,
+}
+
+impl CommandIndex {
+    pub async fn scan() -> Self {
+        if liblog::is_flatpak() {
+            return Self::scan_host().await;
+        }
+        // a long PATH takes a while to read, so keep it off the executor
+        tokio::task::spawn_blocking(Self::scan_local)
+            .await
+            .unwrap_or_default()
+    }
+
+    fn scan_local() -> Self {
+        let mut programs = HashMap::new();
+        let path = std::env::var_os("PATH").unwrap_or_default();
+        for dir in std::env::split_paths(&path) {
+            let Ok(entries) = std::fs::read_dir(dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                // follows symlinks, which most of /usr/bin and the shims in ~/.local/bin are
+                let Ok(metadata) = std::fs::metadata(entry.path()) else {
+                    continue;
+                };
+                if !metadata.is_file() {
+                    continue;
+                }
+                let executable = metadata.permissions().mode() & 0o111 != 0;
+                let name = entry.file_name().to_string_lossy().into_owned();
+                // earlier PATH entries win, as they would in the shell
+                programs.entry(name).or_insert(executable);
+            }
+        }
+
+        Self {
+            programs,
+            host: false,
+        }
+    }
+
+    async fn scan_host() -> Self {
+        let mut programs = HashMap::new();
+        match host_scan_output().await {
+            Ok(output) => {
+                for line in output.lines() {
+                    let Some((mark, path)) = line.split_once(' ') else {
+                        continue;
+                    };
+                    if let Some(name) = Path::new(path).file_name() {
+                        programs
+                            .entry(name.to_string_lossy().into_owned())
+                            .or_insert(mark == "x");
+                    }
+                }
+            }
+            Err(e) => eprintln!("cosmic-ext-applet-logomenu ERROR: '{}'", e),
+        }
+
+        Self {
+            programs,
+            host: true,
+        }
+    }
+
+    pub fn check(&self, command: &str) -> CommandCheck {
+        // the settings app lives next to the applet, wherever that is
+        if liblog::is_settings_command(command) {
+            return CommandCheck::Ok;
+        }
+
+        let Some(program) = program(command) else {
+            return CommandCheck::Invalid;
+        };
+
+        if program.contains('/') {
+            if self.host {
+                return CommandCheck::Ok;
+            }
+            return match std::fs::metadata(expand_home(&program)) {
+                Ok(metadata) if metadata.permissions().mode() & 0o111 != 0 => CommandCheck::Ok,
+                Ok(_) => CommandCheck::NotExecutable(program),
+                Err(_) => CommandCheck::NotFound(program, Vec::new()),
+            };
+        }
+
+        match self.programs.get(&program) {
+            Some(true) => CommandCheck::Ok,
+            Some(false) => CommandCheck::NotExecutable(program),
+            None => {
+                let suggestions = self.suggestions(&program);
+                CommandCheck::NotFound(program, suggestions)
+            }
+        }
+    }
+
+    // Installed programs with names close to the missing one
+    fn suggestions(&self, program: &str) -> Vec<String> {
+        let limit = (program.chars().count() / 3).max(2);
+        let mut matches: Vec<(usize, &String)> = self
+            .programs
+            .iter()
+            .filter(|(_, executable)| **executable)
+            .map(|(name, _)| (distance(program, name), name))
+            .filter(|(distance, _)| *distance <= limit)
+            .collect();
+        matches.sort();
+
+        matches
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, name)| name.clone())
+            .collect()
+    }
+}
+
+// Runs HOST_SCAN on the host, reading its output on a thread so a full pipe
+// never stalls it. The scan dies with the settings app, and is stopped early
+// if it prints more than any PATH could hold.
+async fn host_scan_output() -> zbus::Result<String> {
+    let (reader, writer) = UnixStream::pair().map_err(io_error)?;
+    let output = flatpak::Output {
+        stdout: Some(File::from(OwnedFd::from(writer))),
+        stderr: None,
+    };
+    let mut child = flatpak::spawn(&["sh", "-c", HOST_SCAN], output, flatpak::WATCH_BUS).await?;
+
+    let (tx, rx) = oneshot::channel();
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        let result = reader
+            .take(MAX_SCAN_OUTPUT)
+            .read_to_end(&mut bytes)
+            .map(|_| bytes);
+        let _ = tx.send(result);
+    });
+
+    let bytes = match rx.await {
+        Ok(Ok(bytes)) if (bytes.len() as u64) < MAX_SCAN_OUTPUT => bytes,
+        result => {
+            child.terminate().await?;
+            return Err(match result {
+                Ok(Err(e)) => io_error(e),
+                _ => zbus::Error::Failure("host command scan was cut short".to_string()),
+            });
+        }
+    };
+    child.wait().await?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn io_error(e: std::io::Error) -> zbus::Error {
+    zbus::Error::InputOutput(e.into())
+}
+
+// The program a command line runs, skipping any leading VAR=value assignments
+fn program(command: &str) -> Option<String> {
+    let words = shell_words::split(command).ok()?;
+    words.into_iter().find(|word| !is_assignment(word))
+}
+
+// The shell expands these before running the command, so check what it would run
+fn expand_home(program: &str) -> String {
+    let rest = ["~/", "$HOME/", "${HOME}/"]
+        .iter()
+        .find_map(|prefix| program.strip_prefix(prefix));
+    match (rest, std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{}/{}", home.trim_end_matches('/'), rest),
+        _ => program.to_owned(),
+    }
+}
+
+fn is_assignment(word: &str) -> bool {
+    match word.split_once('=') {
+        Some((name, _)) => {
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        None => false,
+    }
+}
+
+// Levenshtein distance
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+
+    row[b.len()]
+}
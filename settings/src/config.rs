@@ -11,6 +11,25 @@ where
     let _tx_result = config_tx.commit();
 }
 
+pub fn update_state<T>(key: &str, state_vers: u64, value: T)
+where
+    T: Serialize,
+{
+    if let Ok(state) = Config::new_state("dev.cappsy.CosmicExtAppletLogoMenu", state_vers) {
+        let _state_set = state.set(key, value);
+    }
+}
+
+pub fn load_state<T>(key: &str, state_vers: u64) -> Option<T>
+where
+    T: DeserializeOwned,
+{
+    Config::new_state("dev.cappsy.CosmicExtAppletLogoMenu", state_vers)
+        .ok()?
+        .get(key)
+        .ok()
+}
+
 pub fn load_config<T>(key: &str, config_vers: u64) -> Option<T>
 where
     T: DeserializeOwned,
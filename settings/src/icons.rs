@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: GPL-3.0-only
+//
+// Icon names available from the current icon theme
+
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const FALLBACK_THEME: &str = "hicolor";
+
+// Every icon name in the theme, the themes it inherits from and hicolor
+pub fn installed(theme: &str) -> Vec<String> {
+    let dirs = icon_dirs();
+
+    let mut themes = vec![theme.to_string()];
+    let mut i = 0;
+    while i < themes.len() {
+        for parent in inherits(&dirs, &themes[i]) {
+            if !themes.contains(&parent) {
+                themes.push(parent);
+            }
+        }
+        i += 1;
+    }
+    if !themes.iter().any(|theme| theme == FALLBACK_THEME) {
+        themes.push(FALLBACK_THEME.to_string());
+    }
+
+    let mut names = BTreeSet::new();
+    for theme in &themes {
+        for dir in &dirs {
+            collect_icons(&dir.join(theme), &mut names);
+        }
+    }
+    names.into_iter().collect()
+}
+
+pub fn is_symbolic(name: &str) -> bool {
+    name.ends_with("-symbolic")
+}
+
+// Base directories icon themes are installed under, highest priority first
+fn icon_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
+        dirs.push(home.join(".icons"));
+        dirs.push(home.join(".local/share/icons"));
+    }
+    if let Some(data_home) = std::env::var_os("XDG_DATA_HOME") {
+        dirs.push(PathBuf::from(data_home).join("icons"));
+    }
+
+    if liblog::is_flatpak() {
+        dirs.push(PathBuf::from("/run/host/usr/local/share/icons"));
+        dirs.push(PathBuf::from("/run/host/usr/share/icons"));
+    }
+    let system = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| String::from("/usr/local/share:/usr/share"));
+    dirs.extend(system.split(':').map(|dir| Path::new(dir).join("icons")));
+
+    // the same directory can be listed twice, and only its first place counts
+    let mut seen = HashSet::new();
+    dirs.retain(|dir| seen.insert(dir.clone()));
+    dirs
+}
+
+// Parent themes named in the theme's index.theme
+fn inherits(dirs: &[PathBuf], theme: &str) -> Vec<String> {
+    for dir in dirs {
+        let Ok(index) = fs::read_to_string(dir.join(theme).join("index.theme")) else {
+            continue;
+        };
+        for line in index.lines() {
+            if let Some(parents) = line.trim().strip_prefix("Inherits=") {
+                return parents
+                    .split(',')
+                    .map(|parent| parent.trim().to_string())
+                    .filter(|parent| !parent.is_empty())
+                    .collect();
+            }
+        }
+        return Vec::new();
+    }
+    Vec::new()
+}
+
+fn collect_icons(dir: &Path, names: &mut BTreeSet<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_icons(&path, names);
+            continue;
+        }
+        let is_image = path
+            .extension()
+            .is_some_and(|ext| ext == "svg" || ext == "png");
+        if let (true, Some(name)) = (is_image, path.file_stem()) {
+            names.insert(name.to_string_lossy().into_owned());
+        }
+    }
+}
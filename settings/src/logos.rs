@@ -0,0 +1,138 @@
+// SPDX-License-Identifier: GPL-3.0-only
+//
+// Logos for the gallery: the built in ones grouped by distro, and installed packs
+
+use liblog::{CONFIG_ID, IMAGES};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+// One distro's logo, in all the variants that ship with the applet
+#[derive(Debug, Clone)]
+pub struct LogoGroup {
+    pub name: String,
+    // IMAGES keys, colour variants before the symbolic one
+    pub variants: Vec<String>,
+}
+
+// An SVG dropped into a logos directory, used as a custom logo
+#[derive(Debug, Clone)]
+pub struct PackLogo {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl LogoGroup {
+    pub fn matches(&self, query: &str) -> bool {
+        self.name.to_lowercase().contains(&query.to_lowercase())
+    }
+}
+
+impl PackLogo {
+    pub fn matches(&self, query: &str) -> bool {
+        self.name.to_lowercase().contains(&query.to_lowercase())
+    }
+}
+
+// "Fedora" and "Fedora (Symbolic)" become one group
+pub fn groups() -> Vec<LogoGroup> {
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for key in IMAGES.keys() {
+        groups
+            .entry(base_name(key).to_string())
+            .or_default()
+            .push(key.to_string());
+    }
+
+    groups
+        .into_iter()
+        .map(|(name, mut variants)| {
+            variants.sort_by_key(|variant| (IMAGES[variant.as_str()].1, variant.clone()));
+            LogoGroup { name, variants }
+        })
+        .collect()
+}
+
+// The variant shown under a logo, e.g. "Symbolic" for "Fedora (Symbolic)"
+pub fn variant_name(key: &str) -> Option<&str> {
+    key.strip_prefix(base_name(key))?
+        .trim()
+        .strip_prefix('(')?
+        .strip_suffix(')')
+}
+
+fn base_name(key: &str) -> &str {
+    match key.rfind(" (") {
+        Some(i) if key.ends_with(')') => &key[..i],
+        _ => key,
+    }
+}
+
+// SVGs in <data dir>/dev.cappsy.CosmicExtAppletLogoMenu/logos, one level of
+// subdirectories deep so packs can keep to their own folder
+pub fn installed_packs() -> Vec<PackLogo> {
+    let mut data_dirs = Vec::new();
+    if let Some(data_home) = std::env::var_os("XDG_DATA_HOME") {
+        data_dirs.push(PathBuf::from(data_home));
+    }
+    // inside the sandbox XDG_DATA_HOME points at the app's own directory
+    if let Some(home) = std::env::var_os("HOME") {
+        data_dirs.push(PathBuf::from(home).join(".local/share"));
+    }
+    if liblog::is_flatpak() {
+        data_dirs.push(PathBuf::from("/run/host/usr/local/share"));
+        data_dirs.push(PathBuf::from("/run/host/usr/share"));
+    } else {
+        let system = std::env::var("XDG_DATA_DIRS")
+            .ok()
+            .filter(|dirs| !dirs.is_empty())
+            .unwrap_or_else(|| String::from("/usr/local/share:/usr/share"));
+        data_dirs.extend(system.split(':').map(PathBuf::from));
+    }
+
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for dir in data_dirs {
+        let dir = dir.join(CONFIG_ID).join("logos");
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+
+    let mut logos = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                let pack = entry.file_name().to_string_lossy().into_owned();
+                let Ok(files) = fs::read_dir(&path) else {
+                    continue;
+                };
+                for file in files.flatten() {
+                    if let Some(logo) = pack_logo(Some(&pack), file.path()) {
+                        logos.push(logo);
+                    }
+                }
+            } else if let Some(logo) = pack_logo(None, path) {
+                logos.push(logo);
+            }
+        }
+    }
+
+    logos.sort_by_key(|logo| logo.name.to_lowercase());
+    logos
+}
+
+fn pack_logo(pack: Option<&str>, path: PathBuf) -> Option<PackLogo> {
+    if path.extension().is_none_or(|ext| ext != "svg") {
+        return None;
+    }
+    let stem = path.file_stem()?.to_string_lossy().into_owned();
+    let name = match pack {
+        Some(pack) => format!("{} / {}", pack, stem),
+        None => stem,
+    };
+    Some(PackLogo { name, path })
+}
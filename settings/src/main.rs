@@ -1,46 +1,38 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 mod app;
+mod apps;
+mod cli;
+mod commands;
 mod config;
-
-use sysinfo::System;
+mod history;
+mod icons;
+mod logos;
 
 fn main() -> cosmic::iced::Result {
-    if is_running() {
-        return Ok(());
-    }
-
     let requested_languages = i18n_embed::DesktopLanguageRequester::requested_languages();
     liblog::init(&requested_languages);
 
+    // Headless commands never open a window
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if cli::is_command(&args) {
+        std::process::exit(cli::run(&args));
+    }
+
+    let action = match cli::window_action(&args) {
+        Ok(action) => action,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
     let settings = cosmic::app::Settings::default().size_limits(
         cosmic::iced::Limits::NONE
             .min_width(360.0)
             .min_height(180.0),
     );
 
-    cosmic::app::run::<app::AppModel>(settings, ())
-}
-
-fn is_running() -> bool {
-    let current_pid = std::process::id();
-    let current_exe = std::env::current_exe()
-        .ok()
-        .and_then(|p| p.canonicalize().ok());
-
-    let mut system = System::new_all();
-    system.refresh_all();
-    system.processes().values().any(|process| {
-        if process.pid().as_u32() == current_pid {
-            return false;
-        }
-
-        if let Some(exe_path) = process.exe().and_then(|p| p.canonicalize().ok()) {
-            if current_exe.as_ref() == Some(&exe_path) {
-                return true;
-            }
-        }
-
-        false
-    })
+    // a second launch hands its action to the open window and exits
+    cosmic::app::run_single_instance::<app::AppModel>(settings, app::Flags { action })
 }